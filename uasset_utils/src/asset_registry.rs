@@ -1,12 +1,18 @@
+use std::collections::{BTreeMap, BTreeSet};
 use std::io::{Read, Seek, Write};
 
-use anyhow::{anyhow, Context, Result};
 use byteorder::{ReadBytesExt, WriteBytesExt, BE, LE};
+use unreal_asset::engine_version::EngineVersion;
 use unreal_asset::{exports::ExportBaseTrait, types::PackageIndex};
 use unreal_asset::{flags::EObjectFlags, reader::ArchiveTrait};
 
+use unicase::eq_ascii;
+
+use crate::error::{Result, UAssetError};
 use crate::paths::pak_path_to_game_path;
 
+/// Deserializes from a reader. Every implementation in this crate only requires [`Read`]
+/// and consumes the input strictly in order, so streaming readers without [`Seek`] work.
 pub trait Readable<R> {
     fn read(reader: &mut R) -> Result<Self>
     where
@@ -38,6 +44,12 @@ impl<R: Read> Readable<R> for NameIndex {
         Ok(NameIndex(reader.read_u32::<LE>()?))
     }
 }
+/// The name without its number
+impl From<NameIndexFlagged> for NameIndex {
+    fn from(name: NameIndexFlagged) -> Self {
+        NameIndex(name.0)
+    }
+}
 impl<W: Write> Writable<W> for NameIndex {
     fn write(&self, writer: &mut W) -> Result<()> {
         writer.write_u32::<LE>(self.0)?;
@@ -45,7 +57,7 @@ impl<W: Write> Writable<W> for NameIndex {
     }
 }
 
-#[derive(Debug, Clone, Copy, PartialEq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 pub struct NameIndexFlagged(pub u32, pub Option<u32>);
 impl<R: Read> Readable<R> for NameIndexFlagged {
     fn read(reader: &mut R) -> Result<Self> {
@@ -70,7 +82,7 @@ impl<W: Write> Writable<W> for NameIndexFlagged {
     }
 }
 
-#[derive(Debug, PartialEq)]
+#[derive(Debug, PartialEq, Eq, Hash)]
 pub struct ExportPath {
     pub object_path: NameIndexFlagged,
     pub package_path: NameIndexFlagged,
@@ -94,6 +106,43 @@ impl<W: Write> Writable<W> for ExportPath {
     }
 }
 
+/// Name fields resolved to strings. Fields only [`AssetData`] has are `None` for
+/// [`ExportPath`].
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ResolvedFields<'a> {
+    pub object_path: &'a str,
+    pub package_path: &'a str,
+    pub asset_class: &'a str,
+    pub package_name: Option<&'a str>,
+    pub asset_name: Option<&'a str>,
+}
+/// Types holding name indices that can be resolved against a [`Names`] table
+pub trait ResolveNames {
+    fn resolve<'a>(&self, names: &'a Names) -> ResolvedFields<'a>;
+}
+impl ResolveNames for ExportPath {
+    fn resolve<'a>(&self, names: &'a Names) -> ResolvedFields<'a> {
+        ResolvedFields {
+            object_path: &names[self.object_path],
+            package_path: &names[self.package_path],
+            asset_class: &names[self.asset_class],
+            package_name: None,
+            asset_name: None,
+        }
+    }
+}
+impl ResolveNames for AssetData {
+    fn resolve<'a>(&self, names: &'a Names) -> ResolvedFields<'a> {
+        ResolvedFields {
+            object_path: &names[self.object_path],
+            package_path: &names[self.package_path],
+            asset_class: &names[self.asset_class],
+            package_name: Some(&names[self.package_name]),
+            asset_name: Some(&names[self.asset_name]),
+        }
+    }
+}
+
 fn read_array<R, T, E, F>(length: u32, reader: &mut R, mut f: F) -> Result<Vec<T>, E>
 where
     F: FnMut(&mut R) -> Result<T, E>,
@@ -110,7 +159,7 @@ where
     Ok(())
 }
 
-#[derive(Debug, PartialEq)]
+#[derive(Debug, Clone, Copy, PartialEq)]
 pub struct Pair {
     pub name: NameIndex,
     pub type_: Type,
@@ -140,6 +189,26 @@ pub struct MapHandle {
     pub num: u16,
     pub pair_begin: u32,
 }
+/// Empty map with numberless keys
+impl Default for MapHandle {
+    fn default() -> Self {
+        Self {
+            has_numberless_keys: true,
+            num: 0,
+            pair_begin: 0,
+        }
+    }
+}
+impl MapHandle {
+    /// Type of the keys: numberless names or names with a number
+    pub fn key_type(&self) -> Type {
+        if self.has_numberless_keys {
+            Type::NumberlessName
+        } else {
+            Type::Name
+        }
+    }
+}
 impl<R: Read> Readable<R> for MapHandle {
     fn read(reader: &mut R) -> Result<Self> {
         let n = reader.read_u64::<LE>()?;
@@ -161,7 +230,7 @@ impl<W: Write> Writable<W> for MapHandle {
     }
 }
 
-#[derive(Debug, Clone, Copy, PartialEq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 pub enum Type {
     AnsiString = 0,
     WideString = 1,
@@ -171,8 +240,19 @@ pub enum Type {
     ExportPath = 5,
     LocalizedText = 6,
 }
+impl Type {
+    pub const ALL: [Type; 7] = [
+        Type::AnsiString,
+        Type::WideString,
+        Type::NumberlessName,
+        Type::Name,
+        Type::NumberlessExportPath,
+        Type::ExportPath,
+        Type::LocalizedText,
+    ];
+}
 impl TryFrom<u32> for Type {
-    type Error = anyhow::Error;
+    type Error = UAssetError;
 
     fn try_from(v: u32) -> Result<Self> {
         match v {
@@ -183,7 +263,74 @@ impl TryFrom<u32> for Type {
             x if x == Type::NumberlessExportPath as u32 => Ok(Type::NumberlessExportPath),
             x if x == Type::ExportPath as u32 => Ok(Type::ExportPath),
             x if x == Type::LocalizedText as u32 => Ok(Type::LocalizedText),
-            _ => Err(anyhow!("invalid AssetRegistry type: {v}")),
+            _ => Err(UAssetError::InvalidType(v)),
+        }
+    }
+}
+
+/// A tag value resolved against the name table and store
+#[derive(Debug, Clone, PartialEq)]
+pub enum TagValue {
+    AnsiString(String),
+    WideString(String),
+    Name(String),
+    ExportPath {
+        object_path: String,
+        package_path: String,
+        asset_class: String,
+    },
+    LocalizedText(String),
+    /// A string value that is a path to an object such as `/Game/Foo/Bar.Bar`
+    SoftObjectPath(String),
+}
+/// Whether a string tag value is a soft object path like `/Game/Foo/Bar.Bar`
+fn is_soft_object_path(value: &str) -> bool {
+    value.starts_with('/') && value.contains('.') && !value.contains(char::is_whitespace)
+}
+/// Splits a text export string like `NSLOCTEXT("Namespace", "Key", "Source")` into its
+/// namespace, key and source string. `None` for other forms such as `INVTEXT("Source")`.
+pub fn parse_localized_text(text: &str) -> Option<(String, String, String)> {
+    let mut rest = text.trim().strip_prefix("NSLOCTEXT(")?;
+    let mut args = vec![];
+    for i in 0..3 {
+        if i > 0 {
+            rest = rest.trim_start().strip_prefix(',')?;
+        }
+        let mut chars = rest.trim_start().strip_prefix('"')?.chars();
+        let mut arg = String::new();
+        loop {
+            match chars.next()? {
+                '"' => break,
+                '\\' => arg.push(chars.next()?),
+                c => arg.push(c),
+            }
+        }
+        args.push(arg);
+        rest = chars.as_str();
+    }
+    if rest.trim() != ")" {
+        return None;
+    }
+    let [namespace, key, source] = args.try_into().ok()?;
+    Some((namespace, key, source))
+}
+impl From<TagValue> for serde_json::Value {
+    fn from(value: TagValue) -> Self {
+        match value {
+            TagValue::AnsiString(s)
+            | TagValue::WideString(s)
+            | TagValue::Name(s)
+            | TagValue::LocalizedText(s)
+            | TagValue::SoftObjectPath(s) => s.into(),
+            TagValue::ExportPath {
+                object_path,
+                package_path,
+                asset_class,
+            } => serde_json::json!({
+                "package": package_path,
+                "object": object_path,
+                "class": asset_class,
+            }),
         }
     }
 }
@@ -232,8 +379,88 @@ impl<W: Write> Writable<W> for AssetData {
         Ok(())
     }
 }
+impl AssetData {
+    pub fn builder() -> AssetDataBuilder {
+        AssetDataBuilder::default()
+    }
+}
 
-#[derive(Debug, PartialEq)]
+/// Builds an [`AssetData`] with empty tags, no bundles, no chunks and no flags unless overridden
+#[derive(Debug, Default)]
+pub struct AssetDataBuilder {
+    object_path: Option<NameIndexFlagged>,
+    package_path: Option<NameIndexFlagged>,
+    asset_class: Option<NameIndexFlagged>,
+    package_name: Option<NameIndexFlagged>,
+    asset_name: Option<NameIndexFlagged>,
+    tags: Option<MapHandle>,
+    bundle_count: u32,
+    chunk_ids: Vec<u32>,
+    flags: u32,
+}
+impl AssetDataBuilder {
+    pub fn object_path(mut self, object_path: NameIndexFlagged) -> Self {
+        self.object_path = Some(object_path);
+        self
+    }
+    pub fn package_path(mut self, package_path: NameIndexFlagged) -> Self {
+        self.package_path = Some(package_path);
+        self
+    }
+    pub fn asset_class(mut self, asset_class: NameIndexFlagged) -> Self {
+        self.asset_class = Some(asset_class);
+        self
+    }
+    pub fn package_name(mut self, package_name: NameIndexFlagged) -> Self {
+        self.package_name = Some(package_name);
+        self
+    }
+    pub fn asset_name(mut self, asset_name: NameIndexFlagged) -> Self {
+        self.asset_name = Some(asset_name);
+        self
+    }
+    pub fn tags(mut self, tags: MapHandle) -> Self {
+        self.tags = Some(tags);
+        self
+    }
+    pub fn bundle_count(mut self, bundle_count: u32) -> Self {
+        self.bundle_count = bundle_count;
+        self
+    }
+    pub fn chunk_ids(mut self, chunk_ids: Vec<u32>) -> Self {
+        self.chunk_ids = chunk_ids;
+        self
+    }
+    pub fn flags(mut self, flags: u32) -> Self {
+        self.flags = flags;
+        self
+    }
+    pub fn build(self) -> Result<AssetData> {
+        Ok(AssetData {
+            object_path: self
+                .object_path
+                .ok_or_else(|| invalid_input("missing object_path"))?,
+            package_path: self
+                .package_path
+                .ok_or_else(|| invalid_input("missing package_path"))?,
+            asset_class: self
+                .asset_class
+                .ok_or_else(|| invalid_input("missing asset_class"))?,
+            package_name: self
+                .package_name
+                .ok_or_else(|| invalid_input("missing package_name"))?,
+            asset_name: self
+                .asset_name
+                .ok_or_else(|| invalid_input("missing asset_name"))?,
+            tags: self.tags.unwrap_or_default(),
+            bundle_count: self.bundle_count,
+            chunk_ids: self.chunk_ids,
+            flags: self.flags,
+        })
+    }
+}
+
+#[derive(Debug, Default, PartialEq)]
 pub struct Dependencies {
     pub dependencies_size: u64,
     pub dependencies: Vec<u32>,
@@ -262,11 +489,40 @@ impl<W: Write> Writable<W> for Dependencies {
     }
 }
 
+/// Dependencies either parsed or kept verbatim when skipped via [`ReadOptions`]
+#[derive(Debug, PartialEq)]
+pub enum DependencySection {
+    Parsed(Dependencies),
+    Raw(Vec<u8>),
+}
+impl<W: Write> Writable<W> for DependencySection {
+    fn write(&self, writer: &mut W) -> Result<()> {
+        match self {
+            DependencySection::Parsed(dependencies) => dependencies.write(writer)?,
+            DependencySection::Raw(bytes) => writer.write_all(bytes)?,
+        }
+        Ok(())
+    }
+}
+
 const MAGIC_START: u32 = 0x12345679;
 const MAGIC_END: u32 = 0x87654321;
 
-#[derive(Debug, PartialEq)]
+fn invalid_input(msg: impl Into<String>) -> UAssetError {
+    UAssetError::InvalidInput(msg.into())
+}
+
+fn read_magic<R: Read>(reader: &mut R, expected: u32) -> Result<()> {
+    let found = reader.read_u32::<LE>()?;
+    if found != expected {
+        return Err(UAssetError::BadMagic { expected, found });
+    }
+    Ok(())
+}
+
+#[derive(Debug, Default, PartialEq)]
 pub struct Store {
+    /// Number of pairs with numbered keys. These are not read so only the count is kept.
     pub pair_count: u32,
     pub texts: Vec<String>,
     pub nbl_names: Vec<NameIndexFlagged>,
@@ -277,9 +533,27 @@ pub struct Store {
     pub wide_strings: Vec<String>,
     pub pairs: Vec<Pair>,
 }
+impl Store {
+    /// Key type of the pairs `tags` points at, derived from the pair table its range fits:
+    /// the numberless pairs read into `pairs` or the numbered pairs counted by `pair_count`.
+    /// The flagged key type is preferred if it fits both, `None` if it fits neither.
+    pub fn key_type(&self, tags: &MapHandle) -> Option<Type> {
+        let end = tags.pair_begin as usize + tags.num as usize;
+        let fits = |key_type: Type| match key_type {
+            Type::NumberlessName => end <= self.pairs.len(),
+            _ => end <= self.pair_count as usize,
+        };
+        let flagged = tags.key_type();
+        let other = match flagged {
+            Type::NumberlessName => Type::Name,
+            _ => Type::NumberlessName,
+        };
+        [flagged, other].into_iter().find(|&t| fits(t))
+    }
+}
 impl<R: Read> Readable<R> for Store {
     fn read(reader: &mut R) -> Result<Self> {
-        assert_eq!(MAGIC_START, reader.read_u32::<LE>()?);
+        read_magic(reader, MAGIC_START)?;
 
         let nbl_names_count = reader.read_u32::<LE>()?;
         let names_count = reader.read_u32::<LE>()?;
@@ -295,8 +569,16 @@ impl<R: Read> Readable<R> for Store {
 
         let _text_bytes = reader.read_u32::<LE>()?;
         let texts = read_array(texts_count, reader, |r| -> Result<String> {
-            let mut chars = vec![0; r.read_u32::<LE>()? as usize - 1];
-            r.read_exact(&mut chars)?;
+            // length includes the null terminator
+            let len = r.read_u32::<LE>()?;
+            let len = len
+                .checked_sub(1)
+                .ok_or_else(|| invalid_input("text has zero length"))?;
+            let mut chars = vec![];
+            r.take(len.into()).read_to_end(&mut chars)?;
+            if chars.len() != len as usize {
+                return Err(UAssetError::Truncated);
+            }
             r.read_u8()?;
             Ok(String::from_utf8_lossy(&chars).into_owned())
         })?;
@@ -323,20 +605,22 @@ impl<R: Read> Readable<R> for Store {
         })?;
 
         let wide_strings = read_array(wide_strings_count, reader, |reader| -> Result<String> {
-            let mut chars = vec![];
+            let mut units = vec![];
             loop {
                 let next = reader.read_u16::<LE>()?;
                 if next == 0 {
                     break;
                 }
-                chars.push(char::from_u32(next.into()).unwrap_or(char::REPLACEMENT_CHARACTER));
+                units.push(next);
             }
-            Ok(chars.iter().collect::<String>())
+            Ok(char::decode_utf16(units)
+                .map(|c| c.unwrap_or(char::REPLACEMENT_CHARACTER))
+                .collect())
         })?;
 
         let pairs = read_array(nbl_pair_count, reader, Pair::read)?;
 
-        assert_eq!(reader.read_u32::<LE>()?, MAGIC_END);
+        read_magic(reader, MAGIC_END)?;
         Ok(Self {
             pair_count,
             texts,
@@ -371,7 +655,7 @@ impl<W: Write> Writable<W> for Store {
         writer.write_u32::<LE>(
             self.wide_strings
                 .iter()
-                .map(|n| n.chars().count() as u32 + 1)
+                .map(|n| n.encode_utf16().count() as u32 + 1)
                 .sum(),
         )?;
 
@@ -402,10 +686,11 @@ impl<W: Write> Writable<W> for Store {
             offset += i.as_bytes().len() as u32 + 1;
         }
 
+        // wide string offsets and sizes are in UTF-16 code units, not bytes or chars
         let mut offset = 0;
         for i in &self.wide_strings {
             writer.write_u32::<LE>(offset)?;
-            offset += i.chars().count() as u32 + 1;
+            offset += i.encode_utf16().count() as u32 + 1;
         }
 
         write_array(writer, &self.ansi_strings, |w, i| {
@@ -415,8 +700,8 @@ impl<W: Write> Writable<W> for Store {
         })?;
 
         write_array(writer, &self.wide_strings, |w, i| {
-            for c in i.chars() {
-                w.write_u16::<LE>(c as u16)?;
+            for c in i.encode_utf16() {
+                w.write_u16::<LE>(c)?;
             }
             w.write_u16::<LE>(0)?;
             Ok(())
@@ -428,19 +713,184 @@ impl<W: Write> Writable<W> for Store {
         Ok(())
     }
 }
+impl Store {
+    /// Coalesces identical entries in each value vector and points every pair at the
+    /// remaining entry. Map handles are untouched as no pairs are added or removed.
+    pub fn dedup_values(&mut self) {
+        let texts = dedup(&mut self.texts);
+        let nbl_names = dedup(&mut self.nbl_names);
+        let names = dedup(&mut self.names);
+        let nbl_export_paths = dedup(&mut self.nbl_export_paths);
+        let export_paths = dedup(&mut self.export_paths);
+        let ansi_strings = dedup(&mut self.ansi_strings);
+        let wide_strings = dedup(&mut self.wide_strings);
+        for pair in &mut self.pairs {
+            let remap = match pair.type_ {
+                Type::AnsiString => &ansi_strings,
+                Type::WideString => &wide_strings,
+                Type::NumberlessName => &nbl_names,
+                Type::Name => &names,
+                Type::NumberlessExportPath => &nbl_export_paths,
+                Type::ExportPath => &export_paths,
+                Type::LocalizedText => &texts,
+            };
+            pair.index = remap[pair.index as usize];
+        }
+    }
+    /// Removes entries of each value vector that no pair points at. Pairs themselves are not
+    /// removed, unused ones should be dropped first.
+    pub fn remove_unused_values(&mut self) {
+        let used = |type_: Type| {
+            self.pairs
+                .iter()
+                .filter(move |p| p.type_ == type_)
+                .map(|p| p.index)
+        };
+        let texts = retain_used(&mut self.texts, used(Type::LocalizedText));
+        let nbl_names = retain_used(&mut self.nbl_names, used(Type::NumberlessName));
+        let names = retain_used(&mut self.names, used(Type::Name));
+        let nbl_export_paths =
+            retain_used(&mut self.nbl_export_paths, used(Type::NumberlessExportPath));
+        let export_paths = retain_used(&mut self.export_paths, used(Type::ExportPath));
+        let ansi_strings = retain_used(&mut self.ansi_strings, used(Type::AnsiString));
+        let wide_strings = retain_used(&mut self.wide_strings, used(Type::WideString));
+        for pair in &mut self.pairs {
+            let remap = match pair.type_ {
+                Type::AnsiString => &ansi_strings,
+                Type::WideString => &wide_strings,
+                Type::NumberlessName => &nbl_names,
+                Type::Name => &names,
+                Type::NumberlessExportPath => &nbl_export_paths,
+                Type::ExportPath => &export_paths,
+                Type::LocalizedText => &texts,
+            };
+            pair.index = remap[pair.index as usize];
+        }
+    }
+}
+/// Drops entries no pair points at, returns the new index of each kept entry
+fn retain_used<T>(values: &mut Vec<T>, used: impl Iterator<Item = u32>) -> Vec<u32> {
+    let mut keep = vec![false; values.len()];
+    for i in used {
+        keep[i as usize] = true;
+    }
+    let mut next = 0;
+    let remap = keep
+        .iter()
+        .map(|&k| {
+            next += k as u32;
+            next - k as u32
+        })
+        .collect();
+    let mut keep = keep.into_iter();
+    values.retain(|_| keep.next().unwrap());
+    remap
+}
+/// Appends `value`, returning its index
+fn push<T>(values: &mut Vec<T>, value: T) -> u32 {
+    values.push(value);
+    values.len() as u32 - 1
+}
+/// Removes duplicates keeping the first occurrence, returns the new index of each old entry
+fn dedup<T: Eq + std::hash::Hash>(values: &mut Vec<T>) -> Vec<u32> {
+    let mut canonical = std::collections::HashMap::new();
+    let remap = std::mem::take(values)
+        .into_iter()
+        .map(|value| {
+            let next = canonical.len() as u32;
+            *canonical.entry(value).or_insert(next)
+        })
+        .collect();
+    let mut unique: Vec<_> = canonical.into_iter().collect();
+    unique.sort_by_key(|&(_, i)| i);
+    values.extend(unique.into_iter().map(|(value, _)| value));
+    remap
+}
 
-#[derive(Debug, PartialEq)]
-pub struct Names(pub indexmap::IndexSet<String>);
+/// Registry name table. The lowercase hash written alongside each name is computed when the
+/// name is added so writing never rehashes.
+#[derive(Debug, Default)]
+pub struct Names {
+    names: indexmap::IndexSet<String>,
+    hashes: Vec<u64>,
+}
+impl Names {
+    pub fn new(names: indexmap::IndexSet<String>) -> Self {
+        let hashes = names.iter().map(|n| name_hash(n)).collect();
+        Self { names, hashes }
+    }
+    /// Index of `name`, appending it if not already present
+    pub fn insert(&mut self, name: &str) -> usize {
+        if let Some(i) = self.names.get_index_of(name) {
+            return i;
+        }
+        self.hashes.push(name_hash(name));
+        self.names.insert_full(name.to_string()).0
+    }
+    pub fn get_index_of(&self, name: &str) -> Option<usize> {
+        self.names.get_index_of(name)
+    }
+    pub fn get_index(&self, index: usize) -> Option<&str> {
+        self.names.get_index(index).map(String::as_str)
+    }
+    pub fn contains(&self, name: &str) -> bool {
+        self.names.contains(name)
+    }
+    pub fn len(&self) -> usize {
+        self.names.len()
+    }
+    pub fn is_empty(&self) -> bool {
+        self.names.is_empty()
+    }
+    pub fn capacity(&self) -> usize {
+        self.names.capacity()
+    }
+    pub fn reserve(&mut self, additional: usize) {
+        self.names.reserve(additional);
+        self.hashes.reserve(additional);
+    }
+    pub fn iter(&self) -> impl DoubleEndedIterator<Item = &str> + ExactSizeIterator {
+        self.names.iter().map(String::as_str)
+    }
+}
+impl PartialEq for Names {
+    /// Names are compared in order as assets refer to them by index
+    fn eq(&self, other: &Self) -> bool {
+        self.iter().eq(other.iter())
+    }
+}
+
+#[cfg(test)]
+thread_local! {
+    static NAME_HASHES_COMPUTED: std::cell::Cell<usize> = const { std::cell::Cell::new(0) };
+}
+
+fn name_hash(name: &str) -> u64 {
+    #[cfg(test)]
+    NAME_HASHES_COMPUTED.with(|c| c.set(c.get() + 1));
+
+    let lower = name.to_ascii_lowercase();
+    if lower.is_ascii() {
+        cityhasher::hash(lower.as_bytes())
+    } else {
+        cityhasher::hash(
+            lower
+                .encode_utf16()
+                .flat_map(|s| s.to_le_bytes())
+                .collect::<Vec<u8>>(),
+        )
+    }
+}
 impl std::ops::Index<NameIndexFlagged> for Names {
     type Output = String;
     fn index(&self, index: NameIndexFlagged) -> &Self::Output {
-        &self.0[index.0 as usize]
+        &self.names[index.0 as usize]
     }
 }
 impl std::ops::Index<NameIndex> for Names {
     type Output = String;
     fn index(&self, index: NameIndex) -> &Self::Output {
-        &self.0[index.0 as usize]
+        &self.names[index.0 as usize]
     }
 }
 
@@ -475,33 +925,121 @@ pub struct AssetRegistry {
     pub names: Names,
     pub store: Store,
     pub asset_data: Vec<AssetData>,
-    pub dependencies: Dependencies,
+    pub dependencies: DependencySection,
+    /// Unrecognized bytes following the dependencies, written back verbatim
+    pub trailing: Vec<u8>,
+}
+
+/// `FAssetRegistryVersion::GUID` as serialized at the start of a registry
+const ASSET_REGISTRY_VERSION_GUID: Guid = [
+    0xe7, 0x9e, 0x7f, 0x71, 0x3a, 0x49, 0xb0, 0xe9, 0x32, 0x91, 0xb3, 0x88, 0x07, 0x81, 0x38, 0x1b,
+];
+
+/// Registry version GUIDs and version numbers with the engine version they indicate. The GUID
+/// is shared across engine versions so the version number is what tells them apart. Only the
+/// layout this crate reads (`FixedTags`, version 8) is listed.
+pub fn known_version_guids() -> &'static [(Guid, u32, EngineVersion)] {
+    &[(ASSET_REGISTRY_VERSION_GUID, 8, EngineVersion::VER_UE4_27)]
+}
+
+#[derive(Debug, Default, Clone, Copy)]
+pub struct ReadOptions {
+    /// Keep everything after the asset data as opaque bytes instead of parsing dependencies
+    pub skip_dependencies: bool,
+    /// Error on header fields that are normally ignored but disagree with the data
+    pub strict: bool,
 }
+
 impl<R: Read> Readable<R> for AssetRegistry {
     fn read(reader: &mut R) -> Result<Self> {
-        let version = Guid::read(reader)?;
+        Self::read_with(reader, ReadOptions::default())
+    }
+}
+fn read_header<R: Read>(reader: &mut R, strict: bool) -> Result<(Guid, u32, u64, Names)> {
+    let version = Guid::read(reader)?;
 
-        let version_int = reader.read_u32::<LE>()?;
-        let name_count = reader.read_u32::<LE>()?;
-        let _num_string_bytes = reader.read_u32::<LE>()?;
-        let hash_version = reader.read_u64::<LE>()?;
+    let version_int = reader.read_u32::<LE>()?;
+    let name_count = reader.read_u32::<LE>()?;
+    let num_string_bytes = reader.read_u32::<LE>()?;
+    let hash_version = reader.read_u64::<LE>()?;
 
-        let _lowercase_hashes = read_array(name_count, reader, R::read_u64::<LE>)?;
-        let name_lengths = read_array(name_count, reader, R::read_i16::<BE>)?;
+    let _lowercase_hashes = read_array(name_count, reader, R::read_u64::<LE>)?;
+    // name headers are the only big endian field in the registry
+    let name_lengths = read_array(name_count, reader, R::read_i16::<BE>)?;
 
-        let names = Names(
-            name_lengths
-                .clone()
-                .into_iter()
-                .map(|l| read_string(reader, l))
-                .collect::<Result<_>>()?,
-        );
+    if strict {
+        let actual: u64 = name_lengths
+            .iter()
+            .map(|&l| match l {
+                0.. => l as u64,
+                _ => (l - i16::MIN) as u64 * 2,
+            })
+            .sum();
+        if actual != num_string_bytes as u64 {
+            return Err(invalid_input(format!(
+                "num_string_bytes is {num_string_bytes} but names take {actual} bytes"
+            )));
+        }
+    }
+
+    let names = Names::new(
+        name_lengths
+            .into_iter()
+            .map(|l| read_string(reader, l))
+            .collect::<Result<_>>()?,
+    );
+    Ok((version, version_int, hash_version, names))
+}
+
+impl AssetRegistry {
+    /// Likely engine version for the registry's version GUID and number, `None` if not
+    /// recognized
+    pub fn engine_hint(&self) -> Option<EngineVersion> {
+        known_version_guids()
+            .iter()
+            .find(|(guid, version, _)| *guid == self.version && *version == self.version_int)
+            .map(|&(_, _, engine)| engine)
+    }
+    /// Read only the header and name table, leaving `reader` positioned at the start of the
+    /// store
+    pub fn read_names_only<R: Read>(reader: &mut R) -> Result<Names> {
+        Ok(read_header(reader, false)?.3)
+    }
+    /// Parses a registry held in memory
+    pub fn read_from_slice(mut bytes: &[u8]) -> Result<Self> {
+        Self::read(&mut bytes)
+    }
+    /// Reading never seeks so any [`Read`] works, including unbuffered streams. Errors are
+    /// wrapped in [`UAssetError::AtOffset`] with the number of bytes consumed when they
+    /// occurred.
+    pub fn read_with<R: Read>(reader: &mut R, options: ReadOptions) -> Result<Self> {
+        let mut reader = CountingReader {
+            inner: reader,
+            count: 0,
+        };
+        Self::read_sections(&mut reader, options).map_err(|error| UAssetError::AtOffset {
+            offset: reader.count,
+            error: Box::new(error),
+        })
+    }
+    fn read_sections<R: Read>(reader: &mut R, options: ReadOptions) -> Result<Self> {
+        let (version, version_int, hash_version, names) = read_header(reader, options.strict)?;
 
         let store = Store::read(reader)?;
 
         let asset_data = read_array(reader.read_u32::<LE>()?, reader, AssetData::read)?;
 
-        let dependencies = Dependencies::read(reader)?;
+        let dependencies = if options.skip_dependencies {
+            let mut raw = vec![];
+            reader.read_to_end(&mut raw)?;
+            DependencySection::Raw(raw)
+        } else {
+            DependencySection::Parsed(Dependencies::read(reader)?)
+        };
+
+        let mut trailing = vec![];
+        reader.read_to_end(&mut trailing)?;
+
         Ok(AssetRegistry {
             version,
             version_int,
@@ -510,11 +1048,396 @@ impl<R: Read> Readable<R> for AssetRegistry {
             store,
             asset_data,
             dependencies,
+            trailing,
         })
     }
+    /// Reads a registry from a compressed stream
+    #[cfg(feature = "compress")]
+    pub fn read_compressed<R: Read>(reader: R, codec: Codec) -> Result<Self> {
+        match codec {
+            Codec::Gzip => Self::read(&mut flate2::read::GzDecoder::new(reader)),
+            Codec::Zstd => Self::read(&mut zstd::Decoder::new(reader)?),
+        }
+    }
+    /// Writes the registry compressed with `codec`
+    #[cfg(feature = "compress")]
+    pub fn write_compressed<W: Write>(&self, writer: W, codec: Codec) -> Result<()> {
+        match codec {
+            Codec::Gzip => {
+                let mut encoder =
+                    flate2::write::GzEncoder::new(writer, flate2::Compression::default());
+                self.write(&mut encoder)?;
+                encoder.finish()?;
+            }
+            Codec::Zstd => {
+                let mut encoder = zstd::Encoder::new(writer, 0)?;
+                self.write(&mut encoder)?;
+                encoder.finish()?;
+            }
+        }
+        Ok(())
+    }
 }
+/// Compression formats accepted by [`AssetRegistry::read_compressed`]
+#[cfg(feature = "compress")]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Codec {
+    Gzip,
+    Zstd,
+}
+/// Errors with [`UAssetError::IndexOutOfRange`] instead of writing a registry with name, pair
+/// or tag indexes that point past the end of their tables
 impl<W: Write> Writable<W> for AssetRegistry {
     fn write(&self, writer: &mut W) -> Result<()> {
+        self.check_indices()?;
+        self.write_sections(writer, |_| {})
+    }
+}
+
+/// Reads a registry, writes it back and checks that reading the output gives the same
+/// registry and that writing that again gives the same bytes, so a parser that reorders
+/// names or pairs is caught. Returns false rather than panicking if `bytes` fails to parse,
+/// so it can be used directly as a fuzz target.
+pub fn roundtrip_invariant(bytes: &[u8]) -> bool {
+    let Ok(registry) = AssetRegistry::read_from_slice(bytes) else {
+        return false;
+    };
+    let Ok(out) = registry.write_to_vec() else {
+        return false;
+    };
+    AssetRegistry::read_from_slice(&out).is_ok_and(|reread| {
+        reread == registry && reread.write_to_vec().is_ok_and(|again| again == out)
+    })
+}
+
+/// Counts bytes read from the inner reader
+struct CountingReader<R> {
+    inner: R,
+    count: u64,
+}
+impl<R: Read> Read for CountingReader<R> {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        let n = self.inner.read(buf)?;
+        self.count += n as u64;
+        Ok(n)
+    }
+}
+
+/// Counts bytes passed through to the inner writer
+struct CountingWriter<W> {
+    inner: W,
+    count: u64,
+}
+impl<W: Write> Write for CountingWriter<W> {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        let n = self.inner.write(buf)?;
+        self.count += n as u64;
+        Ok(n)
+    }
+    fn flush(&mut self) -> std::io::Result<()> {
+        self.inner.flush()
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct RegistryStats {
+    pub asset_count: usize,
+    pub name_count: usize,
+    pub pair_count: usize,
+    pub estimated_bytes: u64,
+}
+
+/// Entry count, serialized bytes and referenced entry count of one store vector
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct VectorStats {
+    pub count: usize,
+    pub bytes: u64,
+    pub referenced: usize,
+}
+
+/// Utilization of each store vector. Values count as referenced when a pair points at them,
+/// pairs when an asset's tags include them.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct StoreStats {
+    pub texts: VectorStats,
+    pub nbl_names: VectorStats,
+    pub names: VectorStats,
+    pub nbl_export_paths: VectorStats,
+    pub export_paths: VectorStats,
+    pub ansi_strings: VectorStats,
+    pub wide_strings: VectorStats,
+    pub pairs: VectorStats,
+}
+
+/// Compact overview of a registry for debug output, from [`AssetRegistry::summary`]. Use
+/// [`dbg::Dbg`] for the full resolved contents.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct RegistrySummary {
+    pub version: u32,
+    pub engine_hint: Option<EngineVersion>,
+    pub name_count: usize,
+    pub asset_count: usize,
+    pub store: StoreStats,
+}
+
+/// How to bring a registry file up to date, from [`AssetRegistry::write_diff_regions`]
+#[derive(Debug, PartialEq)]
+pub enum RegistryUpdate {
+    /// Byte ranges to overwrite in place, as (offset, bytes). The file length is unchanged.
+    Patch(Vec<(usize, Vec<u8>)>),
+    /// The whole registry, as the change could not be expressed in place
+    Rewrite(Vec<u8>),
+}
+
+fn vector_stats<T>(
+    values: &[T],
+    size: impl Fn(&T) -> u64,
+    used: impl Iterator<Item = usize>,
+) -> VectorStats {
+    let mut referenced = vec![false; values.len()];
+    for i in used {
+        if let Some(r) = referenced.get_mut(i) {
+            *r = true;
+        }
+    }
+    VectorStats {
+        count: values.len(),
+        bytes: values.iter().map(size).sum(),
+        referenced: referenced.into_iter().filter(|r| *r).count(),
+    }
+}
+
+fn name_size(n: &NameIndexFlagged) -> u64 {
+    if n.1.is_some() {
+        8
+    } else {
+        4
+    }
+}
+fn export_path_size(p: &ExportPath) -> u64 {
+    name_size(&p.object_path) + name_size(&p.package_path) + name_size(&p.asset_class)
+}
+
+impl AssetRegistry {
+    /// Cheap summary of the registry's current size without serializing it. Use
+    /// [`AssetRegistry::serialized_size`] when exact byte counts are needed.
+    pub fn pending_stats(&self) -> RegistryStats {
+        // guid, version, name count, string bytes, hash version
+        let mut size = 16 + 4 + 4 + 4 + 8;
+        for n in self.names.iter() {
+            let bytes = if n.is_ascii() {
+                n.len()
+            } else {
+                n.encode_utf16().count() * 2
+            };
+            size += 8 + 2 + bytes as u64;
+        }
+
+        let s = &self.store;
+        // magics, counts and text bytes
+        size += 4 * 14;
+        size += s.texts.iter().map(|t| 4 + t.len() as u64 + 1).sum::<u64>();
+        size += s
+            .nbl_names
+            .iter()
+            .chain(&s.names)
+            .map(name_size)
+            .sum::<u64>();
+        size += s.nbl_export_paths.iter().map(export_path_size).sum::<u64>();
+        size += s.export_paths.iter().map(export_path_size).sum::<u64>();
+        size += s
+            .ansi_strings
+            .iter()
+            .map(|a| 4 + a.len() as u64 + 1)
+            .sum::<u64>();
+        for w in &s.wide_strings {
+            size += 4 + 2 * (w.encode_utf16().count() as u64 + 1);
+        }
+        size += 8 * s.pairs.len() as u64;
+
+        size += 4;
+        for a in &self.asset_data {
+            size +=
+                name_size(&a.object_path) + name_size(&a.package_path) + name_size(&a.asset_class);
+            size += name_size(&a.package_name) + name_size(&a.asset_name);
+            // tags, bundle count, chunk ids, flags
+            size += 8 + 4 + 4 + 4 * a.chunk_ids.len() as u64 + 4;
+        }
+
+        size += match &self.dependencies {
+            DependencySection::Parsed(d) => 8 + 4 + 4 * d.dependencies.len() as u64 + 4,
+            DependencySection::Raw(raw) => raw.len() as u64,
+        };
+        size += self.trailing.len() as u64;
+
+        RegistryStats {
+            asset_count: self.asset_data.len(),
+            name_count: self.names.len(),
+            pair_count: self.store.pairs.len(),
+            estimated_bytes: size,
+        }
+    }
+
+    pub fn asset_count(&self) -> usize {
+        self.asset_data.len()
+    }
+    /// Version, sizes and store utilization without the contents, for `dbg!` on large
+    /// registries
+    pub fn summary(&self) -> RegistrySummary {
+        RegistrySummary {
+            version: self.version_int,
+            engine_hint: self.engine_hint(),
+            name_count: self.names.len(),
+            asset_count: self.asset_count(),
+            store: self.store_stats(),
+        }
+    }
+    /// Per vector breakdown of the store, showing entries [`Store::dedup_values`] or
+    /// removing unused pairs could drop
+    pub fn store_stats(&self) -> StoreStats {
+        let s = &self.store;
+        let used = |type_: Type| {
+            s.pairs
+                .iter()
+                .filter(move |p| p.type_ == type_)
+                .map(|p| p.index as usize)
+        };
+        StoreStats {
+            texts: vector_stats(
+                &s.texts,
+                |t| 4 + t.len() as u64 + 1,
+                used(Type::LocalizedText),
+            ),
+            nbl_names: vector_stats(&s.nbl_names, name_size, used(Type::NumberlessName)),
+            names: vector_stats(&s.names, name_size, used(Type::Name)),
+            nbl_export_paths: vector_stats(
+                &s.nbl_export_paths,
+                export_path_size,
+                used(Type::NumberlessExportPath),
+            ),
+            export_paths: vector_stats(&s.export_paths, export_path_size, used(Type::ExportPath)),
+            ansi_strings: vector_stats(
+                &s.ansi_strings,
+                |a| 4 + a.len() as u64 + 1,
+                used(Type::AnsiString),
+            ),
+            wide_strings: vector_stats(
+                &s.wide_strings,
+                |w| 4 + 2 * (w.encode_utf16().count() as u64 + 1),
+                used(Type::WideString),
+            ),
+            pairs: vector_stats(
+                &s.pairs,
+                |_| 8,
+                self.asset_data.iter().flat_map(|a| {
+                    let begin = a.tags.pair_begin as usize;
+                    begin..begin + a.tags.num as usize
+                }),
+            ),
+        }
+    }
+
+    /// Number of bytes [`Writable::write`] would produce
+    pub fn serialized_size(&self) -> Result<u64> {
+        let mut counter = CountingWriter {
+            inner: std::io::sink(),
+            count: 0,
+        };
+        self.write(&mut counter)?;
+        Ok(counter.count)
+    }
+
+    /// Serializes the registry into a buffer sized up front from [`AssetRegistry::pending_stats`]
+    pub fn write_to_vec(&self) -> Result<Vec<u8>> {
+        let mut buf = Vec::with_capacity(self.pending_stats().estimated_bytes as usize);
+        self.write(&mut buf)?;
+        Ok(buf)
+    }
+    /// Same as [`Writable::write`] but skips the index checks
+    pub fn write_unchecked<W: Write>(&self, writer: &mut W) -> Result<()> {
+        self.write_sections(writer, |_| {})
+    }
+
+    /// Writes the registry, calling `progress` with (bytes written, total bytes) after the
+    /// names, store, asset data and dependencies sections
+    pub fn write_with_progress<W: Write>(
+        &self,
+        writer: &mut W,
+        mut progress: impl FnMut(u64, u64),
+    ) -> Result<()> {
+        let total = self.serialized_size()?;
+        let mut writer = CountingWriter {
+            inner: writer,
+            count: 0,
+        };
+        self.write_sections(&mut writer, |w| progress(w.count, total))
+    }
+
+    /// Compares against the registry serialized in `original`. If only the chunk ids (keeping
+    /// their count) and flags of existing assets changed, returns patches for just those
+    /// fields instead of serializing the whole registry.
+    pub fn write_diff_regions(&self, original: &[u8]) -> Result<RegistryUpdate> {
+        let old = Self::read_from_slice(original)?;
+        let same_layout = old.version == self.version
+            && old.version_int == self.version_int
+            && old.hash_version == self.hash_version
+            // every index shifts when names are reordered
+            && old.names.iter().eq(self.names.iter())
+            && old.store == self.store
+            && old.dependencies == self.dependencies
+            && old.trailing == self.trailing
+            && old.asset_data.len() == self.asset_data.len()
+            && old.asset_data.iter().zip(&self.asset_data).all(|(a, b)| {
+                a.object_path == b.object_path
+                    && a.package_path == b.package_path
+                    && a.asset_class == b.asset_class
+                    && a.package_name == b.package_name
+                    && a.asset_name == b.asset_name
+                    && a.tags == b.tags
+                    && a.bundle_count == b.bundle_count
+                    && a.chunk_ids.len() == b.chunk_ids.len()
+            });
+        if !same_layout {
+            return Ok(RegistryUpdate::Rewrite(self.write_to_vec()?));
+        }
+
+        let mut reader = CountingReader {
+            inner: original,
+            count: 0,
+        };
+        read_header(&mut reader, false)?;
+        Store::read(&mut reader)?;
+        // past the asset count
+        let mut offset = reader.count as usize + 4;
+
+        let mut patches = vec![];
+        for (old, new) in old.asset_data.iter().zip(&self.asset_data) {
+            offset += [
+                &new.object_path,
+                &new.package_path,
+                &new.asset_class,
+                &new.package_name,
+                &new.asset_name,
+            ]
+            .into_iter()
+            .map(|n| name_size(n) as usize)
+            .sum::<usize>();
+            // tags, bundle count, chunk id count
+            offset += 8 + 4 + 4;
+            if old.chunk_ids != new.chunk_ids {
+                let bytes = new.chunk_ids.iter().flat_map(|c| c.to_le_bytes()).collect();
+                patches.push((offset, bytes));
+            }
+            offset += 4 * new.chunk_ids.len();
+            if old.flags != new.flags {
+                patches.push((offset, new.flags.to_le_bytes().to_vec()));
+            }
+            offset += 4;
+        }
+        Ok(RegistryUpdate::Patch(patches))
+    }
+
+    fn write_sections<W: Write>(&self, writer: &mut W, mut section: impl FnMut(&W)) -> Result<()> {
         self.version.write(writer)?;
 
         // TODO don't call encode_utf16 multiple times for each name
@@ -528,25 +1451,15 @@ impl<W: Write> Writable<W> for AssetRegistry {
         }
 
         writer.write_u32::<LE>(self.version_int)?;
-        writer.write_u32::<LE>(self.names.0.len() as u32)?;
-        writer.write_u32::<LE>(self.names.0.iter().map(size).sum())?;
+        writer.write_u32::<LE>(self.names.len() as u32)?;
+        writer.write_u32::<LE>(self.names.iter().map(size).sum())?;
         writer.write_u64::<LE>(self.hash_version)?;
 
-        write_array(writer, &self.names.0, |w, i| {
-            let lower = i.to_ascii_lowercase();
-            let hash = if lower.is_ascii() {
-                cityhasher::hash(lower.as_bytes())
-            } else {
-                cityhasher::hash(
-                    lower
-                        .encode_utf16()
-                        .flat_map(|s| s.to_le_bytes())
-                        .collect::<Vec<u8>>(),
-                )
-            };
-            Ok(w.write_u64::<LE>(hash)?)
+        write_array(writer, &self.names.hashes, |w, hash| {
+            Ok(w.write_u64::<LE>(*hash)?)
         })?;
-        write_array(writer, &self.names.0, |w, i| {
+        // name headers are the only big endian field in the registry
+        write_array(writer, self.names.iter(), |w, i| {
             let len = if i.is_ascii() {
                 i.as_bytes().len() as i16
             } else {
@@ -555,14 +1468,19 @@ impl<W: Write> Writable<W> for AssetRegistry {
             Ok(w.write_i16::<BE>(len)?)
         })?;
 
-        write_array(writer, &self.names.0, |w, i| write_string(w, i))?;
+        write_array(writer, self.names.iter(), write_string)?;
+        section(writer);
 
         self.store.write(writer)?;
+        section(writer);
 
         writer.write_u32::<LE>(self.asset_data.len() as u32)?;
         write_array(writer, &self.asset_data, |w, i| i.write(w))?;
+        section(writer);
 
         self.dependencies.write(writer)?;
+        writer.write_all(&self.trailing)?;
+        section(writer);
 
         Ok(())
     }
@@ -586,12 +1504,13 @@ pub mod dbg {
 
     impl Debug for Dbg<'_, '_, AssetData> {
         fn fmt(&self, f: &mut Formatter<'_>) -> Result {
+            let names = self.data.resolve(&self.reg.names);
             f.debug_struct("AssetData")
-                .field("object_path", &self.reg.names[self.data.object_path])
-                .field("package_path", &self.reg.names[self.data.package_path])
-                .field("asset_class", &self.reg.names[self.data.asset_class])
-                .field("package_name", &self.reg.names[self.data.package_name])
-                .field("asset_name", &self.reg.names[self.data.asset_name])
+                .field("object_path", &names.object_path)
+                .field("package_path", &names.package_path)
+                .field("asset_class", &names.asset_class)
+                .field("package_name", &names.package_name.unwrap())
+                .field("asset_name", &names.asset_name.unwrap())
                 .field("tags", &Dbg::new(self.reg, &self.data.tags))
                 .field("bundle_count", &self.data.bundle_count)
                 .field("chunk_ids", &self.data.chunk_ids)
@@ -601,10 +1520,11 @@ pub mod dbg {
     }
     impl Debug for Dbg<'_, '_, ExportPath> {
         fn fmt(&self, f: &mut Formatter<'_>) -> Result {
+            let names = self.data.resolve(&self.reg.names);
             f.debug_struct("Asset")
-                .field("object_path", &self.reg.names[self.data.object_path])
-                .field("package_path", &self.reg.names[self.data.package_path])
-                .field("asset_class", &self.reg.names[self.data.asset_class])
+                .field("object_path", &names.object_path)
+                .field("package_path", &names.package_path)
+                .field("asset_class", &names.asset_class)
                 .finish()
         }
     }
@@ -665,107 +1585,2863 @@ pub fn get_root_export<C: Read + Seek>(
     None
 }
 
-impl AssetRegistry {
-    pub fn get_name(&mut self, name: &str) -> NameIndexFlagged {
-        if let Some(i) = self.names.0.get_index_of(name) {
-            NameIndexFlagged(i as u32, None)
-        } else {
-            self.names.0.insert(name.to_string());
-            NameIndexFlagged(self.names.0.len() as u32 - 1, None)
-        }
-    }
-    pub fn populate<C: Read + Seek>(
-        &mut self,
-        path: &str,
-        asset: &unreal_asset::Asset<C>,
-    ) -> Result<()> {
-        let game_path = crate::paths::PakPathBuf::from(
-            pak_path_to_game_path(path).context("failed to get game path")?,
-        );
+/// Registry strings describing an asset, as derived by [`registry_fields_for`]
+#[derive(Debug, Clone, PartialEq)]
+pub struct RegistryFields {
+    pub object_path: String,
+    pub package_path: String,
+    pub asset_class: String,
+    pub package_name: String,
+    pub asset_name: String,
+}
+/// Derives the fields [`AssetRegistry::populate`] stores for an asset at a `/Game/...` path
+pub fn registry_fields_for(
+    game_path: &str,
+    asset_name: &str,
+    asset_class: &str,
+) -> Result<RegistryFields> {
+    let game_path = crate::paths::PakPath::new(game_path);
+    Ok(RegistryFields {
+        object_path: crate::paths::object_path(game_path.as_str(), asset_name),
+        package_path: game_path
+            .parent()
+            .ok_or_else(|| invalid_input("no path parent"))?
+            .as_str()
+            .to_string(),
+        asset_class: asset_class.to_string(),
+        package_name: game_path.as_str().to_string(),
+        asset_name: asset_name.to_string(),
+    })
+}
+fn populate_fields<C: Read + Seek>(
+    game_path: &str,
+    asset: &unreal_asset::Asset<C>,
+) -> Result<RegistryFields> {
+    let root = get_root_export(asset).ok_or_else(|| invalid_input("no root export"))?;
+    let root = asset.get_export(root).unwrap();
 
-        let root = get_root_export(asset).context("no root export")?;
-        let root = asset.get_export(root).unwrap();
+    let asset_name = root.get_base_export().object_name.get_owned_content();
+    let asset_class = asset
+        .get_import(root.get_base_export().class_index)
+        .ok_or_else(|| invalid_input("bad import ref"))?
+        .object_name
+        .get_owned_content();
+    registry_fields_for(game_path, &asset_name, &asset_class)
+}
 
-        let asset_name_str = root.get_base_export().object_name.get_owned_content();
-        let package_path_str = game_path.parent().context("no path parent")?.as_str();
-        let package_name_str = game_path.as_str();
-        let object_path_str = format!("{game_path}.{asset_name_str}");
-        let asset_class_str = asset
-            .get_import(root.get_base_export().class_index)
-            .context("bad import ref")?
-            .object_name
-            .get_owned_content();
+/// Splits `/Game/Foo/Bar.Bar` into package path, package name and asset name. The asset name
+/// of a sub-object path like `/Game/Foo/Bar.Bar:Component` is the sub-object's name.
+fn split_object_path(object_path: &str) -> Option<(&str, &str, &str)> {
+    let path = crate::paths::parse_object_path(object_path)?;
+    let (package_path, _) = path.package_name.rsplit_once('/')?;
+    Some((package_path, path.package_name, path.object_name()))
+}
 
-        // skip if existing entry
-        // TODO performs linear scan through to find a match
-        // quite fast since it's just an integer comparision but can be faster with a map
-        if let Some(name_index) = self.names.0.get_index_of(&object_path_str) {
-            if self
-                .asset_data
-                .iter()
-                .any(|a| a.object_path.0 as usize == name_index)
-            {
-                return Ok(());
-            }
-        }
+/// An asset to add with [`AssetRegistry::apply_patch`]
+#[derive(Debug, Clone, PartialEq)]
+pub struct AssetSpec {
+    /// Object path such as `/Game/Foo/Bar.Bar`, the other paths and names are derived from it
+    pub object_path: String,
+    pub asset_class: String,
+}
 
-        let object_path = self.get_name(&object_path_str);
-        let package_path = self.get_name(package_path_str);
-        let asset_class = self.get_name(&asset_class_str);
-        let package_name = self.get_name(package_name_str);
-        let asset_name = self.get_name(&asset_name_str);
+/// Edits applied together by [`AssetRegistry::apply_patch`]. Assets are referred to by object
+/// path.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct RegistryPatch {
+    pub add: Vec<AssetSpec>,
+    pub remove: Vec<String>,
+    pub set_chunks: Vec<(String, Vec<u32>)>,
+    /// Tags replacing any existing values with the same keys. Only string values can be
+    /// stored.
+    pub set_tags: Vec<(String, Vec<(String, TagValue)>)>,
+}
 
-        let new = AssetData {
-            object_path,
-            package_path,
-            asset_class,
-            package_name,
-            asset_name,
-            tags: MapHandle {
-                has_numberless_keys: true,
-                num: 0,
-                pair_begin: 0,
-            },
-            bundle_count: 0,
-            chunk_ids: vec![],
-            flags: 0,
+/// Tag keys only present in development cooks, removed by
+/// [`AssetRegistry::strip_editor_only`]
+pub const EDITOR_ONLY_TAGS: &[&str] = &["AssetImportData", "FiBData"];
+
+/// An [`AssetData`] paired with the registry its names resolve against
+#[derive(Debug, Clone, Copy)]
+pub struct ResolvedAsset<'a> {
+    pub registry: &'a AssetRegistry,
+    pub data: &'a AssetData,
+}
+impl<'a> ResolvedAsset<'a> {
+    pub fn object_path(&self) -> &'a str {
+        &self.registry.names[self.data.object_path]
+    }
+    pub fn package_path(&self) -> &'a str {
+        &self.registry.names[self.data.package_path]
+    }
+    pub fn asset_class(&self) -> &'a str {
+        &self.registry.names[self.data.asset_class]
+    }
+    pub fn package_name(&self) -> &'a str {
+        &self.registry.names[self.data.package_name]
+    }
+    pub fn asset_name(&self) -> &'a str {
+        &self.registry.names[self.data.asset_name]
+    }
+    pub fn tag_value(&self, key: &str) -> Option<TagValue> {
+        self.registry.tag_value(self.data, key)
+    }
+    /// Tag keys and their resolved values in store order. Maps with numbered keys yield
+    /// nothing as their pairs are not read.
+    pub fn tags(&self) -> impl Iterator<Item = (&'a str, TagValue)> + 'a {
+        let registry = self.registry;
+        registry
+            .numberless_pairs(&self.data.tags)
+            .unwrap_or_default()
+            .iter()
+            .map(move |p| (registry.names[p.name].as_str(), registry.pair_value(p)))
+    }
+    pub fn tags_json(&self) -> serde_json::Map<String, serde_json::Value> {
+        self.tags()
+            .map(|(key, value)| (key.to_string(), value.into()))
+            .collect()
+    }
+}
+
+impl AssetRegistry {
+    pub fn get_name(&mut self, name: &str) -> NameIndexFlagged {
+        NameIndexFlagged(self.names.insert(name) as u32, None)
+    }
+    /// Reserves room for at least `additional` more names
+    pub fn reserve_names(&mut self, additional: usize) {
+        self.names.reserve(additional);
+    }
+    /// Reserves room for at least `additional` more assets
+    pub fn reserve_assets(&mut self, additional: usize) {
+        self.asset_data.reserve(additional);
+    }
+    pub fn populate<C: Read + Seek>(
+        &mut self,
+        path: &str,
+        asset: &unreal_asset::Asset<C>,
+    ) -> Result<()> {
+        let game_path =
+            pak_path_to_game_path(path).ok_or_else(|| invalid_input("failed to get game path"))?;
+        self.populate_game_path(&game_path, asset)
+    }
+    /// Same as [`AssetRegistry::populate`] but takes an already resolved `/Game/...` path
+    pub fn populate_game_path<C: Read + Seek>(
+        &mut self,
+        game_path: &str,
+        asset: &unreal_asset::Asset<C>,
+    ) -> Result<()> {
+        self.insert_populated(populate_fields(game_path, asset)?, 0)
+    }
+    /// Same as [`AssetRegistry::populate`] but sets the asset's bundle count. Bundle entries
+    /// are not read from the asset, only the count is stored.
+    pub fn populate_with_bundle_count<C: Read + Seek>(
+        &mut self,
+        path: &str,
+        asset: &unreal_asset::Asset<C>,
+        bundle_count: u32,
+    ) -> Result<()> {
+        let game_path =
+            pak_path_to_game_path(path).ok_or_else(|| invalid_input("failed to get game path"))?;
+        self.insert_populated(populate_fields(&game_path, asset)?, bundle_count)
+    }
+    /// Like calling [`AssetRegistry::populate`] for each entry but parses assets in parallel
+    /// before adding them in order
+    #[cfg(feature = "rayon")]
+    pub fn populate_many<C: Read + Seek + Send>(
+        &mut self,
+        entries: impl rayon::iter::ParallelIterator<Item = (String, unreal_asset::Asset<C>)>,
+    ) -> Result<()> {
+        self.insert_populated_many(entries.map(|(path, asset)| {
+            let game_path = pak_path_to_game_path(&path)
+                .ok_or_else(|| invalid_input("failed to get game path"))?;
+            populate_fields(&game_path, &asset)
+        }))
+    }
+    /// Collects fields extracted in parallel, then adds them in their original order as the
+    /// name table can't be shared between threads
+    #[cfg(feature = "rayon")]
+    fn insert_populated_many(
+        &mut self,
+        fields: impl rayon::iter::ParallelIterator<Item = Result<RegistryFields>>,
+    ) -> Result<()> {
+        for fields in fields.collect::<Result<Vec<_>>>()? {
+            self.insert_populated(fields, 0)?;
+        }
+        Ok(())
+    }
+    /// Fields of the assets [`AssetRegistry::populate`] would add for `asset`, including the
+    /// Blueprint added alongside a generated class, without changing the registry. Empty if
+    /// the asset is already registered.
+    pub fn preview_populate<C: Read + Seek>(
+        &self,
+        path: &str,
+        asset: &unreal_asset::Asset<C>,
+    ) -> Result<Vec<RegistryFields>> {
+        let game_path =
+            pak_path_to_game_path(path).ok_or_else(|| invalid_input("failed to get game path"))?;
+        Ok(self.preview_populated(populate_fields(&game_path, asset)?))
+    }
+    fn preview_populated(&self, fields: RegistryFields) -> Vec<RegistryFields> {
+        // skip if existing entry
+        if self.find_by_object_path(&fields.object_path).is_some() {
+            return vec![];
+        }
+        let twin = match (
+            fields.asset_name.strip_suffix("_C"),
+            fields.object_path.strip_suffix("_C"),
+            fields.asset_class.strip_suffix("GeneratedClass"),
+        ) {
+            (Some(asset_name), Some(object_path), Some(asset_class)) => Some(RegistryFields {
+                object_path: object_path.to_string(),
+                package_path: fields.package_path.clone(),
+                asset_class: asset_class.to_string(),
+                package_name: fields.package_name.clone(),
+                asset_name: asset_name.to_string(),
+            }),
+            _ => None,
+        };
+        std::iter::once(fields).chain(twin).collect()
+    }
+    fn insert_populated(&mut self, fields: RegistryFields, bundle_count: u32) -> Result<()> {
+        for (i, fields) in self.preview_populated(fields).into_iter().enumerate() {
+            let new = AssetData::builder()
+                .object_path(self.get_name(&fields.object_path))
+                .package_path(self.get_name(&fields.package_path))
+                .asset_class(self.get_name(&fields.asset_class))
+                .package_name(self.get_name(&fields.package_name))
+                .asset_name(self.get_name(&fields.asset_name))
+                // the bundle count only applies to the populated asset, not its twin
+                .bundle_count(if i == 0 { bundle_count } else { 0 })
+                .build()?;
+            self.asset_data.push(new);
+        }
+        Ok(())
+    }
+
+    /// Checks that every asset's tag map points at pairs with the key type it is flagged with
+    /// and that every index is in range
+    pub fn validate(&self) -> Result<()> {
+        for (i, asset) in self.asset_data.iter().enumerate() {
+            let tags = &asset.tags;
+            if tags.num == 0 {
+                continue;
+            }
+            // maps fitting neither pair table are reported as out of range below
+            match self.store.key_type(tags) {
+                Some(key_type) if key_type != tags.key_type() => {
+                    return Err(invalid_input(format!(
+                        "asset {i} tags are flagged {:?} but point at {key_type:?} pairs",
+                        tags.key_type()
+                    )));
+                }
+                _ => {}
+            }
+        }
+        self.check_indices()
+    }
+    /// Checks that every name index, pair value index and tag map is in range. Maps with
+    /// numbered keys are checked against the numbered pair count as their pairs are not read.
+    fn check_indices(&self) -> Result<()> {
+        let name_count = self.names.len();
+        let check_name = |n: u32, what: &dyn Fn() -> String| {
+            if n as usize >= name_count {
+                return Err(UAssetError::IndexOutOfRange(format!(
+                    "{} name {n} out of range of {name_count} names",
+                    what()
+                )));
+            }
+            Ok(())
+        };
+        let check_export_path = |p: &ExportPath, what: &dyn Fn() -> String| {
+            check_name(p.object_path.0, what)?;
+            check_name(p.package_path.0, what)?;
+            check_name(p.asset_class.0, what)
+        };
+
+        for (i, asset) in self.asset_data.iter().enumerate() {
+            let what = || format!("asset {i}");
+            for n in [
+                asset.object_path,
+                asset.package_path,
+                asset.asset_class,
+                asset.package_name,
+                asset.asset_name,
+            ] {
+                check_name(n.0, &what)?;
+            }
+            let tags = &asset.tags;
+            if tags.num == 0 {
+                continue;
+            }
+            let (len, pairs) = match tags.key_type() {
+                Type::NumberlessName => (self.store.pairs.len(), "pairs"),
+                _ => (self.store.pair_count as usize, "numbered pairs"),
+            };
+            let end = tags.pair_begin as usize + tags.num as usize;
+            if end > len {
+                return Err(UAssetError::IndexOutOfRange(format!(
+                    "asset {i} tags {}..{end} out of range of {len} {pairs}",
+                    tags.pair_begin,
+                )));
+            }
+        }
+
+        let s = &self.store;
+        for (i, n) in s.nbl_names.iter().chain(&s.names).enumerate() {
+            check_name(n.0, &|| format!("store name {i}"))?;
+        }
+        for (i, p) in s.nbl_export_paths.iter().chain(&s.export_paths).enumerate() {
+            check_export_path(p, &|| format!("store export path {i}"))?;
+        }
+        for (i, pair) in s.pairs.iter().enumerate() {
+            check_name(pair.name.0, &|| format!("pair {i} key"))?;
+            let len = match pair.type_ {
+                Type::AnsiString => s.ansi_strings.len(),
+                Type::WideString => s.wide_strings.len(),
+                Type::NumberlessName => s.nbl_names.len(),
+                Type::Name => s.names.len(),
+                Type::NumberlessExportPath => s.nbl_export_paths.len(),
+                Type::ExportPath => s.export_paths.len(),
+                Type::LocalizedText => s.texts.len(),
+            };
+            if pair.index as usize >= len {
+                return Err(UAssetError::IndexOutOfRange(format!(
+                    "pair {i} {:?} value {} out of range of {len}",
+                    pair.type_, pair.index
+                )));
+            }
+        }
+        Ok(())
+    }
+
+    /// Index into `asset_data` of the asset with the given object path
+    // TODO performs linear scan through to find a match
+    // quite fast since it's just an integer comparision but can be faster with a map
+    fn asset_index(&self, object_path: &str) -> Option<usize> {
+        let name_index = self.names.get_index_of(object_path)? as u32;
+        self.asset_data
+            .iter()
+            .position(|a| a.object_path.0 == name_index)
+    }
+    /// Finds an asset by exact object path. Sub-objects indexed by the registry are found by
+    /// their full `package.asset:subobject` path.
+    pub fn find_by_object_path(&self, object_path: &str) -> Option<&AssetData> {
+        self.asset_index(object_path).map(|i| &self.asset_data[i])
+    }
+    /// Whether any asset has the given package name, ignoring ASCII case. An exact match is
+    /// looked up in the name table before falling back to a case-insensitive scan.
+    pub fn contains_package(&self, package_name: &str) -> bool {
+        let has_name = |i: usize| {
+            self.asset_data
+                .iter()
+                .any(|a| a.package_name.0 as usize == i)
+        };
+        if let Some(i) = self.names.get_index_of(package_name) {
+            if has_name(i) {
+                return true;
+            }
+        }
+        self.names
+            .iter()
+            .enumerate()
+            .any(|(i, name)| eq_ascii(name, package_name) && has_name(i))
+    }
+    pub fn resolve<'a>(&'a self, data: &'a AssetData) -> ResolvedAsset<'a> {
+        ResolvedAsset {
+            registry: self,
+            data,
+        }
+    }
+    pub fn assets(&self) -> impl Iterator<Item = ResolvedAsset<'_>> {
+        self.asset_data.iter().map(|data| self.resolve(data))
+    }
+    /// Keeps only assets matching the predicate and drops the tag pairs of removed assets.
+    /// Names are left in the name table as the dependency section may still reference them.
+    pub fn retain<F: Fn(&ResolvedAsset) -> bool>(&mut self, f: F) {
+        let keep: Vec<bool> = self.assets().map(|a| f(&a)).collect();
+        let mut keep = keep.into_iter();
+        self.asset_data.retain(|_| keep.next().unwrap());
+        self.remove_unused_pairs();
+    }
+    /// New registry holding only the assets whose package name starts with `prefix`, such as
+    /// `/Game/ModA/`, with a name table and store containing just what they use. Dependencies
+    /// are not decoded so no edges can be carried over and the section is left empty, as are
+    /// trailing bytes. Tags of maps with numbered keys are dropped as their pairs are not read,
+    /// as are maps pointing outside the store and pairs whose value is out of range.
+    pub fn subset(&self, prefix: &str) -> AssetRegistry {
+        let mut subset = AssetRegistry {
+            version: self.version,
+            version_int: self.version_int,
+            hash_version: self.hash_version,
+            names: Names::new(Default::default()),
+            store: Store::default(),
+            asset_data: vec![],
+            dependencies: DependencySection::Parsed(Dependencies::default()),
+            trailing: vec![],
+        };
+        let name = |subset: &mut AssetRegistry, n: NameIndexFlagged| {
+            NameIndexFlagged(subset.get_name(&self.names[n]).0, n.1)
+        };
+        let export_path = |subset: &mut AssetRegistry, p: &ExportPath| ExportPath {
+            object_path: name(subset, p.object_path),
+            package_path: name(subset, p.package_path),
+            asset_class: name(subset, p.asset_class),
         };
-        self.asset_data.push(new);
 
-        if let (Some(asset_name_str), Some(object_path_str), Some(asset_class_str)) = (
-            asset_name_str.strip_suffix("_C"),
-            object_path_str.strip_suffix("_C"),
-            asset_class_str.strip_suffix("GeneratedClass"),
+        for asset in &self.asset_data {
+            if !self.names[asset.package_name].starts_with(prefix) {
+                continue;
+            }
+            let mut tags = MapHandle {
+                pair_begin: subset.store.pairs.len() as u32,
+                ..Default::default()
+            };
+            for pair in self.numberless_pairs(&asset.tags).into_iter().flatten() {
+                let s = &self.store;
+                let i = pair.index as usize;
+                let index = match pair.type_ {
+                    Type::AnsiString => s
+                        .ansi_strings
+                        .get(i)
+                        .map(|v| push(&mut subset.store.ansi_strings, v.clone())),
+                    Type::WideString => s
+                        .wide_strings
+                        .get(i)
+                        .map(|v| push(&mut subset.store.wide_strings, v.clone())),
+                    Type::LocalizedText => s
+                        .texts
+                        .get(i)
+                        .map(|v| push(&mut subset.store.texts, v.clone())),
+                    Type::NumberlessName => s.nbl_names.get(i).map(|&n| {
+                        let n = name(&mut subset, n);
+                        push(&mut subset.store.nbl_names, n)
+                    }),
+                    Type::Name => s.names.get(i).map(|&n| {
+                        let n = name(&mut subset, n);
+                        push(&mut subset.store.names, n)
+                    }),
+                    Type::NumberlessExportPath => s.nbl_export_paths.get(i).map(|p| {
+                        let p = export_path(&mut subset, p);
+                        push(&mut subset.store.nbl_export_paths, p)
+                    }),
+                    Type::ExportPath => s.export_paths.get(i).map(|p| {
+                        let p = export_path(&mut subset, p);
+                        push(&mut subset.store.export_paths, p)
+                    }),
+                };
+                let Some(index) = index else {
+                    continue;
+                };
+                let key = NameIndex(subset.get_name(&self.names[pair.name]).0);
+                subset.store.pairs.push(Pair {
+                    name: key,
+                    type_: pair.type_,
+                    index,
+                });
+                tags.num += 1;
+            }
+            let data = AssetData {
+                object_path: name(&mut subset, asset.object_path),
+                package_path: name(&mut subset, asset.package_path),
+                asset_class: name(&mut subset, asset.asset_class),
+                package_name: name(&mut subset, asset.package_name),
+                asset_name: name(&mut subset, asset.asset_name),
+                tags,
+                bundle_count: asset.bundle_count,
+                chunk_ids: asset.chunk_ids.clone(),
+                flags: asset.flags,
+            };
+            subset.asset_data.push(data);
+        }
+        subset
+    }
+    /// Removes tags with any of the given keys, ignoring ASCII case, from every asset then
+    /// drops store entries left unused. Maps with numbered keys or pointing outside the store
+    /// are not changed.
+    pub fn strip_tags(&mut self, keys: &[&str]) {
+        let strip: Vec<bool> = self
+            .store
+            .pairs
+            .iter()
+            .map(|p| {
+                keys.iter()
+                    .any(|k| eq_ascii(self.names[p.name].as_str(), k))
+            })
+            .collect();
+
+        // new index of each pair, plus one past the end
+        let mut new_index = Vec::with_capacity(strip.len() + 1);
+        let mut next = 0;
+        for &s in &strip {
+            new_index.push(next);
+            next += !s as u32;
+        }
+        new_index.push(next);
+        for asset in &mut self.asset_data {
+            if asset.tags.key_type() != Type::NumberlessName || asset.tags.num == 0 {
+                continue;
+            }
+            let begin = asset.tags.pair_begin as usize;
+            let end = begin + asset.tags.num as usize;
+            // maps outside the store are left as they are
+            let (Some(&new_begin), Some(&new_end)) = (new_index.get(begin), new_index.get(end))
+            else {
+                continue;
+            };
+            asset.tags.pair_begin = new_begin;
+            asset.tags.num = (new_end - new_begin) as u16;
+        }
+
+        let mut strip = strip.into_iter();
+        self.store.pairs.retain(|_| !strip.next().unwrap());
+        self.remove_unused_pairs();
+        self.store.remove_unused_values();
+    }
+    /// Removes the [`EDITOR_ONLY_TAGS`] that shipping cooks strip
+    pub fn strip_editor_only(&mut self) {
+        self.strip_tags(EDITOR_ONLY_TAGS);
+    }
+    /// Removes pairs not referenced by any asset, updating each `pair_begin` to match. Maps
+    /// with numbered keys index pairs that are not read and are left as they are, as are
+    /// maps pointing outside the store.
+    fn remove_unused_pairs(&mut self) {
+        let pairs = self.store.pairs.len();
+        let in_store = |tags: &MapHandle| {
+            tags.key_type() == Type::NumberlessName
+                && tags.num > 0
+                && tags.pair_begin as usize + tags.num as usize <= pairs
+        };
+        let mut used = vec![false; pairs];
+        for asset in self.asset_data.iter().filter(|a| in_store(&a.tags)) {
+            let begin = asset.tags.pair_begin as usize;
+            used[begin..begin + asset.tags.num as usize].fill(true);
+        }
+
+        let mut new_index = Vec::with_capacity(used.len());
+        let mut next = 0;
+        for &u in &used {
+            new_index.push(next);
+            next += u as u32;
+        }
+        for asset in self.asset_data.iter_mut().filter(|a| in_store(&a.tags)) {
+            asset.tags.pair_begin = new_index[asset.tags.pair_begin as usize];
+        }
+
+        let mut used = used.into_iter();
+        self.store.pairs.retain(|_| used.next().unwrap());
+    }
+    /// Number of references to a name from asset data, store names and export paths, and
+    /// pair keys, counting pairs no asset uses. The undecoded dependency section is not
+    /// scanned.
+    pub fn name_usage_count(&self, idx: NameIndex) -> usize {
+        let s = &self.store;
+        let export_paths = s.nbl_export_paths.iter().chain(&s.export_paths);
+        let names = self
+            .asset_data
+            .iter()
+            .flat_map(|a| {
+                [
+                    a.object_path,
+                    a.package_path,
+                    a.asset_class,
+                    a.package_name,
+                    a.asset_name,
+                ]
+            })
+            .chain(s.nbl_names.iter().chain(&s.names).copied())
+            .chain(export_paths.flat_map(|p| [p.object_path, p.package_path, p.asset_class]));
+        names.filter(|n| n.0 == idx.0).count() + s.pairs.iter().filter(|p| p.name == idx).count()
+    }
+    /// Distinct package names across all assets
+    pub fn package_names(&self) -> BTreeSet<String> {
+        self.asset_data
+            .iter()
+            .map(|a| self.names[a.package_name].clone())
+            .collect()
+    }
+    /// Compares the registry against the files in a pak. Returns the package names listed in
+    /// the registry but missing from the pak (dangling) and the game paths of pak packages
+    /// missing from the registry (unregistered). Paths are compared case-insensitively and
+    /// only `.uasset` and `.umap` files are considered.
+    pub fn diff_pak_files<'a>(
+        &self,
+        pak_files: impl IntoIterator<Item = &'a str>,
+    ) -> (Vec<String>, Vec<String>) {
+        let in_pak: BTreeMap<String, String> = pak_files
+            .into_iter()
+            .filter_map(|file| {
+                let (package, extension) = file.rsplit_once('.')?;
+                if !eq_ascii(extension, "uasset") && !eq_ascii(extension, "umap") {
+                    return None;
+                }
+                let game_path = pak_path_to_game_path(package)?;
+                Some((game_path.to_ascii_lowercase(), game_path))
+            })
+            .collect();
+        let in_registry: BTreeMap<String, String> = self
+            .package_names()
+            .into_iter()
+            .map(|name| (name.to_ascii_lowercase(), name))
+            .collect();
+
+        let dangling = in_registry
+            .iter()
+            .filter(|(key, _)| !in_pak.contains_key(*key))
+            .map(|(_, name)| name.clone())
+            .collect();
+        let unregistered = in_pak
+            .iter()
+            .filter(|(key, _)| !in_registry.contains_key(*key))
+            .map(|(_, name)| name.clone())
+            .collect();
+        (dangling, unregistered)
+    }
+    /// Writes one CSV row per asset with its object path, package name, asset class,
+    /// semicolon separated chunk ids and flags, after a header row
+    pub fn to_csv<W: Write>(&self, w: &mut W) -> Result<()> {
+        fn field(value: &str) -> std::borrow::Cow<'_, str> {
+            if value.contains([',', '"', '\n', '\r']) {
+                format!("\"{}\"", value.replace('"', "\"\"")).into()
+            } else {
+                value.into()
+            }
+        }
+        writeln!(w, "object_path,package_name,asset_class,chunk_ids,flags")?;
+        for asset in &self.asset_data {
+            let chunk_ids: Vec<_> = asset.chunk_ids.iter().map(|c| c.to_string()).collect();
+            writeln!(
+                w,
+                "{},{},{},{},{}",
+                field(&self.names[asset.object_path]),
+                field(&self.names[asset.package_name]),
+                field(&self.names[asset.asset_class]),
+                chunk_ids.join(";"),
+                asset.flags,
+            )?;
+        }
+        Ok(())
+    }
+    /// Object paths of the assets in each chunk. Assets in several chunks are listed under
+    /// each and assets without chunk ids are omitted.
+    pub fn chunk_map(&self) -> BTreeMap<u32, Vec<String>> {
+        let mut map: BTreeMap<u32, Vec<String>> = BTreeMap::new();
+        for asset in &self.asset_data {
+            for &chunk in &asset.chunk_ids {
+                map.entry(chunk)
+                    .or_default()
+                    .push(self.names[asset.object_path].clone());
+            }
+        }
+        map
+    }
+    /// Distinct asset classes across all assets
+    pub fn classes(&self) -> BTreeSet<String> {
+        self.asset_data
+            .iter()
+            .map(|a| self.names[a.asset_class].clone())
+            .collect()
+    }
+    /// Number of assets of each asset class
+    pub fn count_by_class(&self) -> BTreeMap<String, usize> {
+        let mut counts = BTreeMap::new();
+        for asset in &self.asset_data {
+            *counts
+                .entry(self.names[asset.asset_class].clone())
+                .or_default() += 1;
+        }
+        counts
+    }
+    /// Number of assets carrying each tag key. Maps with numbered keys are skipped as their
+    /// pairs are not read from the store, as are maps pointing outside the store.
+    pub fn tag_key_histogram(&self) -> BTreeMap<String, usize> {
+        let mut histogram = BTreeMap::new();
+        for asset in &self.asset_data {
+            for pair in self.numberless_pairs(&asset.tags).into_iter().flatten() {
+                *histogram.entry(self.names[pair.name].clone()).or_default() += 1;
+            }
+        }
+        histogram
+    }
+    /// Namespace, key and source string of every `NSLOCTEXT` text tag value, in order of
+    /// first appearance. Repeated namespace and key pairs are only listed once and maps with
+    /// numbered keys are skipped.
+    pub fn localized_texts(&self) -> Vec<(String, String, String)> {
+        let mut seen = std::collections::HashSet::new();
+        let mut texts = vec![];
+        for asset in &self.asset_data {
+            let pairs = self.numberless_pairs(&asset.tags).unwrap_or_default();
+            for pair in pairs {
+                if pair.type_ != Type::LocalizedText {
+                    continue;
+                }
+                let text = self.store.texts.get(pair.index as usize);
+                let Some(text) = text.and_then(|t| parse_localized_text(t)) else {
+                    continue;
+                };
+                if seen.insert((text.0.clone(), text.1.clone())) {
+                    texts.push(text);
+                }
+            }
+        }
+        texts
+    }
+    /// Hash of the resolved assets and their tags, independent of name table and store order.
+    /// Maps with numbered keys contribute their handle as their pairs are not read.
+    pub fn content_hash(&self) -> u64 {
+        let mut assets: Vec<String> = self
+            .asset_data
+            .iter()
+            .map(|a| {
+                let tags = match self.numberless_pairs(&a.tags) {
+                    Some(pairs) => {
+                        let mut tags: Vec<String> = pairs
+                            .iter()
+                            .map(|p| format!("{}={:?}", self.names[p.name], self.pair_value(p)))
+                            .collect();
+                        tags.sort();
+                        tags.join("|")
+                    }
+                    None => format!("{:?}", a.tags),
+                };
+                format!(
+                    "{}|{}|{}|{}|{}|{}|{:?}|{}|{}",
+                    self.names[a.object_path],
+                    self.names[a.package_path],
+                    self.names[a.asset_class],
+                    self.names[a.package_name],
+                    self.names[a.asset_name],
+                    a.bundle_count,
+                    a.chunk_ids,
+                    a.flags,
+                    tags,
+                )
+            })
+            .collect();
+        assets.sort();
+        cityhasher::hash(assets.join("\n"))
+    }
+    /// Assigns an asset to chunks. Blueprints and their generated class (`_C`) are kept in
+    /// sync. Returns false if no asset has the given object path.
+    pub fn set_chunk_ids(&mut self, object_path: &str, chunk_ids: &[u32]) -> bool {
+        let twin = match object_path.strip_suffix("_C") {
+            Some(blueprint) => blueprint.to_string(),
+            None => format!("{object_path}_C"),
+        };
+        let mut found = false;
+        for path in [object_path, &twin] {
+            if let Some(i) = self.asset_index(path) {
+                self.asset_data[i].chunk_ids = chunk_ids.to_vec();
+                found = true;
+            }
+        }
+        found
+    }
+    /// Moves an asset to a new object path, deriving its package path, package name and asset
+    /// name from it. The Blueprint or generated class (`_C`) twin is moved along with it.
+    /// Returns false if the asset doesn't exist or the new path is taken by another asset.
+    pub fn rename_asset(&mut self, old_object_path: &str, new_object_path: &str) -> bool {
+        let Some(index) = self.asset_index(old_object_path) else {
+            return false;
+        };
+        if old_object_path == new_object_path {
+            return true;
+        }
+        let (old_twin, new_twin) = match (
+            old_object_path.strip_suffix("_C"),
+            new_object_path.strip_suffix("_C"),
         ) {
-            let new = AssetData {
-                object_path: self.get_name(object_path_str),
-                package_path,
-                asset_class: self.get_name(asset_class_str),
-                package_name,
-                asset_name: self.get_name(asset_name_str),
-                tags: MapHandle {
-                    has_numberless_keys: true,
-                    num: 0,
-                    pair_begin: 0,
-                },
-                bundle_count: 0,
-                chunk_ids: vec![],
-                flags: 0,
+            (Some(old), Some(new)) => (old.to_string(), new.to_string()),
+            _ => (
+                format!("{old_object_path}_C"),
+                format!("{new_object_path}_C"),
+            ),
+        };
+        let twin = self.asset_index(&old_twin);
+        if self.asset_index(new_object_path).is_some()
+            || twin.is_some() && self.asset_index(&new_twin).is_some()
+        {
+            return false;
+        }
+        let renames: Vec<_> = [(Some(index), new_object_path), (twin, &new_twin)]
+            .into_iter()
+            .filter_map(|(i, new)| Some((i?, new, split_object_path(new))))
+            .collect();
+        if renames.iter().any(|(_, _, parts)| parts.is_none()) {
+            return false;
+        }
+
+        for (i, new, parts) in renames {
+            let (package_path, package_name, asset_name) = parts.unwrap();
+            let object_path = self.get_name(new);
+            let package_path = self.get_name(package_path);
+            let package_name = self.get_name(package_name);
+            let asset_name = self.get_name(asset_name);
+            let asset = &mut self.asset_data[i];
+            asset.object_path = object_path;
+            asset.package_path = package_path;
+            asset.package_name = package_name;
+            asset.asset_name = asset_name;
+        }
+        true
+    }
+
+    /// Resolves the value a pair points to in the store
+    pub fn pair_value(&self, pair: &Pair) -> TagValue {
+        let s = &self.store;
+        let i = pair.index as usize;
+        let export_path = |p: &ExportPath| {
+            let names = p.resolve(&self.names);
+            TagValue::ExportPath {
+                object_path: names.object_path.to_string(),
+                package_path: names.package_path.to_string(),
+                asset_class: names.asset_class.to_string(),
+            }
+        };
+        let string = |value: &String, plain: fn(String) -> TagValue| {
+            if is_soft_object_path(value) {
+                TagValue::SoftObjectPath(value.clone())
+            } else {
+                plain(value.clone())
+            }
+        };
+        match pair.type_ {
+            Type::AnsiString => string(&s.ansi_strings[i], TagValue::AnsiString),
+            Type::WideString => string(&s.wide_strings[i], TagValue::WideString),
+            Type::NumberlessName => TagValue::Name(self.names[s.nbl_names[i]].clone()),
+            Type::Name => TagValue::Name(self.names[s.names[i]].clone()),
+            Type::NumberlessExportPath => export_path(&s.nbl_export_paths[i]),
+            Type::ExportPath => export_path(&s.export_paths[i]),
+            Type::LocalizedText => TagValue::LocalizedText(s.texts[i].clone()),
+        }
+    }
+    /// Every pair in the store with its key and value, whether or not an asset uses it
+    pub fn iter_pairs(&self) -> impl Iterator<Item = (&str, TagValue)> + '_ {
+        self.store
+            .pairs
+            .iter()
+            .map(|p| (self.names[p.name].as_str(), self.pair_value(p)))
+    }
+    /// Pairs of a tag map, `None` if its keys are numbered as those pairs are not read from
+    /// the store, or if it points outside the store. Empty maps have no pairs wherever they
+    /// point.
+    fn numberless_pairs(&self, tags: &MapHandle) -> Option<&[Pair]> {
+        if tags.key_type() != Type::NumberlessName {
+            return None;
+        }
+        if tags.num == 0 {
+            return Some(&[]);
+        }
+        let begin = tags.pair_begin as usize;
+        self.store.pairs.get(begin..begin + tags.num as usize)
+    }
+    /// Looks up an asset's tag by key, ignoring ASCII case like UE does for names
+    pub fn tag_value(&self, asset: &AssetData, key: &str) -> Option<TagValue> {
+        self.numberless_pairs(&asset.tags)?
+            .iter()
+            .find(|p| eq_ascii(self.names[p.name].as_str(), key))
+            .map(|p| self.pair_value(p))
+    }
+
+    /// Appends a string valued pair to the store, stored as [`Type::AnsiString`] when the
+    /// value is ASCII and as [`Type::WideString`] otherwise
+    pub fn push_pair(&mut self, key: &str, value: &str) -> Pair {
+        let type_ = if value.is_ascii() {
+            Type::AnsiString
+        } else {
+            Type::WideString
+        };
+        self.push_pair_as(key, value, type_).unwrap()
+    }
+    /// Appends a string valued pair to the store with an explicit string or text type
+    pub fn push_pair_as(&mut self, key: &str, value: &str, type_: Type) -> Result<Pair> {
+        let name = NameIndex(self.get_name(key).0);
+        let strings = match type_ {
+            Type::AnsiString if value.is_ascii() => &mut self.store.ansi_strings,
+            Type::AnsiString => {
+                return Err(invalid_input(format!(
+                    "cannot store non-ASCII value as AnsiString: {value:?}"
+                )))
+            }
+            Type::WideString => &mut self.store.wide_strings,
+            Type::LocalizedText => &mut self.store.texts,
+            _ => return Err(invalid_input(format!("{type_:?} is not a string type"))),
+        };
+        strings.push(value.to_string());
+        let pair = Pair {
+            name,
+            type_,
+            index: strings.len() as u32 - 1,
+        };
+        self.store.pairs.push(pair);
+        Ok(pair)
+    }
+    /// Appends string valued tags to an asset. Existing pairs are moved to the end of the
+    /// store first if needed so the asset's pairs stay contiguous. Assets whose tags have
+    /// numbered keys are rejected as their pairs are not read.
+    pub fn add_tags<'a>(
+        &mut self,
+        asset: usize,
+        tags: impl IntoIterator<Item = (&'a str, &'a str)>,
+    ) -> Result<()> {
+        let handle = &self.asset_data[asset].tags;
+        if handle.num > 0 {
+            if handle.key_type() != Type::NumberlessName {
+                return Err(invalid_input(format!(
+                    "asset {asset} has tags with numbered keys"
+                )));
+            }
+            if self.numberless_pairs(handle).is_none() {
+                return Err(UAssetError::IndexOutOfRange(format!(
+                    "asset {asset} tags {}..{} out of range of {} pairs",
+                    handle.pair_begin,
+                    handle.pair_begin as usize + handle.num as usize,
+                    self.store.pairs.len()
+                )));
+            }
+        }
+        let old = handle.pair_begin as usize..handle.pair_begin as usize + handle.num as usize;
+        let begin = if handle.num == 0 {
+            self.store.pairs.len()
+        } else if old.end == self.store.pairs.len() {
+            old.start
+        } else {
+            let begin = self.store.pairs.len();
+            self.store.pairs.extend_from_within(old);
+            begin
+        };
+        for (key, value) in tags {
+            self.push_pair(key, value);
+        }
+        self.asset_data[asset].tags = MapHandle {
+            has_numberless_keys: true,
+            num: (self.store.pairs.len() - begin)
+                .try_into()
+                .map_err(|_| invalid_input("too many tags"))?,
+            pair_begin: begin as u32,
+        };
+        Ok(())
+    }
+
+    /// Adds, removes, then updates chunks and tags of assets as described by `patch`. The
+    /// whole patch is checked first so an error leaves the registry unchanged.
+    pub fn apply_patch(&mut self, patch: RegistryPatch) -> Result<()> {
+        let mut present: BTreeSet<&str> = self.assets().map(|a| a.object_path()).collect();
+        for spec in &patch.add {
+            if split_object_path(&spec.object_path).is_none() {
+                return Err(invalid_input(format!(
+                    "invalid object path {:?}",
+                    spec.object_path
+                )));
+            }
+            if !present.insert(&spec.object_path) {
+                return Err(invalid_input(format!(
+                    "asset {:?} already exists",
+                    spec.object_path
+                )));
+            }
+        }
+        for path in &patch.remove {
+            if !present.remove(path.as_str()) {
+                return Err(invalid_input(format!("no asset {path:?} to remove")));
+            }
+        }
+        let edited = patch.set_chunks.iter().map(|(path, _)| path);
+        for path in edited.chain(patch.set_tags.iter().map(|(path, _)| path)) {
+            if !present.contains(path.as_str()) {
+                return Err(invalid_input(format!("no asset {path:?}")));
+            }
+        }
+        // keys each edited asset ends up with, so the tag count is checked before any change
+        let mut keys: BTreeMap<&str, Vec<&str>> = BTreeMap::new();
+        for (path, tags) in &patch.set_tags {
+            let current = match keys.entry(path.as_str()) {
+                std::collections::btree_map::Entry::Occupied(e) => e.into_mut(),
+                std::collections::btree_map::Entry::Vacant(e) => {
+                    let existing = match self.find_by_object_path(path) {
+                        Some(a) if a.tags.key_type() != Type::NumberlessName => {
+                            return Err(invalid_input(format!(
+                                "asset {path:?} has tags with numbered keys"
+                            )))
+                        }
+                        Some(a) => self
+                            .numberless_pairs(&a.tags)
+                            .ok_or_else(|| {
+                                invalid_input(format!("asset {path:?} tags are out of range"))
+                            })?
+                            .iter()
+                            .map(|p| self.names[p.name].as_str())
+                            .collect(),
+                        // added by this patch
+                        None => vec![],
+                    };
+                    e.insert(existing)
+                }
             };
-            self.asset_data.push(new);
+            let replaced: BTreeSet<&str> = tags.iter().map(|(key, _)| key.as_str()).collect();
+            current.retain(|k| !replaced.contains(k));
+            current.extend(tags.iter().map(|(key, _)| key.as_str()));
+            if current.len() > u16::MAX as usize {
+                return Err(invalid_input(format!("too many tags for asset {path:?}")));
+            }
+            for (key, value) in tags {
+                match value {
+                    TagValue::AnsiString(s) if !s.is_ascii() => {
+                        return Err(invalid_input(format!(
+                            "cannot store non-ASCII value as AnsiString: {s:?}"
+                        )))
+                    }
+                    TagValue::AnsiString(_)
+                    | TagValue::WideString(_)
+                    | TagValue::SoftObjectPath(_) => {}
+                    _ => {
+                        return Err(invalid_input(format!(
+                            "cannot store tag {key:?} value {value:?}"
+                        )))
+                    }
+                }
+            }
+        }
+
+        for spec in &patch.add {
+            let (package_path, package_name, asset_name) =
+                split_object_path(&spec.object_path).unwrap();
+            let data = AssetData::builder()
+                .object_path(self.get_name(&spec.object_path))
+                .package_path(self.get_name(package_path))
+                .asset_class(self.get_name(&spec.asset_class))
+                .package_name(self.get_name(package_name))
+                .asset_name(self.get_name(asset_name))
+                .build()?;
+            self.asset_data.push(data);
+        }
+        let remove: BTreeSet<&str> = patch.remove.iter().map(String::as_str).collect();
+        self.retain(|a| !remove.contains(a.object_path()));
+        let index = |ar: &AssetRegistry, path: &str| {
+            ar.asset_index(path)
+                .ok_or_else(|| invalid_input(format!("no asset {path:?}")))
+        };
+        for (path, chunk_ids) in &patch.set_chunks {
+            let i = index(self, path)?;
+            self.asset_data[i].chunk_ids = chunk_ids.clone();
+        }
+        for (path, tags) in &patch.set_tags {
+            let i = index(self, path)?;
+            let kept: Vec<Pair> = self
+                .numberless_pairs(&self.asset_data[i].tags)
+                .ok_or_else(|| invalid_input(format!("asset {path:?} tags are out of range")))?
+                .iter()
+                .filter(|p| !tags.iter().any(|(key, _)| self.names[p.name] == *key))
+                .copied()
+                .collect();
+            let begin = self.store.pairs.len();
+            self.store.pairs.extend(kept);
+            for (key, value) in tags {
+                match value {
+                    TagValue::AnsiString(s) => self.push_pair_as(key, s, Type::AnsiString)?,
+                    TagValue::WideString(s) => self.push_pair_as(key, s, Type::WideString)?,
+                    TagValue::SoftObjectPath(s) => self.push_pair(key, s),
+                    _ => unreachable!("tag values are checked above"),
+                };
+            }
+            self.asset_data[i].tags = MapHandle {
+                has_numberless_keys: true,
+                num: (self.store.pairs.len() - begin)
+                    .try_into()
+                    .map_err(|_| invalid_input("too many tags"))?,
+                pair_begin: begin as u32,
+            };
+        }
+        self.remove_unused_pairs();
+        Ok(())
+    }
+
+    /// Rewrites the object, package and package name paths of every asset as well as any
+    /// name, export path or soft object path tag values. `f` returns the new path or `None` to
+    /// leave it as is.
+    pub fn remap_paths<F: Fn(&str) -> Option<String>>(&mut self, f: F) {
+        fn remap<F: Fn(&str) -> Option<String>>(
+            names: &mut Names,
+            name: NameIndexFlagged,
+            f: &F,
+        ) -> NameIndexFlagged {
+            match f(&names[name]) {
+                Some(new) => NameIndexFlagged(names.insert(&new) as u32, name.1),
+                None => name,
+            }
+        }
+
+        let names = &mut self.names;
+        for asset in &mut self.asset_data {
+            asset.object_path = remap(names, asset.object_path, &f);
+            asset.package_path = remap(names, asset.package_path, &f);
+            asset.package_name = remap(names, asset.package_name, &f);
+        }
+        let store = &mut self.store;
+        for name in store.nbl_names.iter_mut().chain(store.names.iter_mut()) {
+            *name = remap(names, *name, &f);
+        }
+        for path in store
+            .nbl_export_paths
+            .iter_mut()
+            .chain(store.export_paths.iter_mut())
+        {
+            path.object_path = remap(names, path.object_path, &f);
+            path.package_path = remap(names, path.package_path, &f);
+        }
+        for (strings, ansi) in [
+            (&mut store.ansi_strings, true),
+            (&mut store.wide_strings, false),
+        ] {
+            for value in strings.iter_mut().filter(|s| is_soft_object_path(s)) {
+                match f(value) {
+                    Some(new) if !ansi || new.is_ascii() => *value = new,
+                    _ => {}
+                }
+            }
         }
+    }
+}
 
+/// Set on the name indexes of an [`OverlayRegistry`] that refer to its own names rather than
+/// the base registry's
+pub const OVERLAY_NAME: u32 = 1 << 31;
+
+/// Assets added on top of a base registry without copying it. Names already in the base are
+/// shared, new names go to `extra_names` and are marked with [`OVERLAY_NAME`]. Lookups check
+/// the added assets before the base.
+#[derive(Debug)]
+pub struct OverlayRegistry<'a> {
+    pub base: &'a AssetRegistry,
+    pub added: Vec<AssetData>,
+    pub extra_names: Names,
+}
+impl<'a> OverlayRegistry<'a> {
+    pub fn new(base: &'a AssetRegistry) -> Self {
+        Self {
+            base,
+            added: vec![],
+            extra_names: Names::new(Default::default()),
+        }
+    }
+    /// Index of `name` in the base if present, otherwise in the overlay's own names
+    pub fn get_name(&mut self, name: &str) -> NameIndexFlagged {
+        if let Some(i) = self.base.names.get_index_of(name) {
+            return NameIndexFlagged(i as u32, None);
+        }
+        let i = self.extra_names.insert(name);
+        NameIndexFlagged(i as u32 | OVERLAY_NAME, None)
+    }
+    pub fn name(&self, name: NameIndexFlagged) -> &str {
+        if name.0 & OVERLAY_NAME != 0 {
+            &self.extra_names.names[(name.0 & !OVERLAY_NAME) as usize]
+        } else {
+            &self.base.names[name]
+        }
+    }
+    /// Adds an asset with no tags, deriving its paths from an object path such as
+    /// `/Game/Foo/Bar.Bar`
+    pub fn add_asset(&mut self, object_path: &str, asset_class: &str) -> Result<()> {
+        if self.find_by_object_path(object_path).is_some() {
+            return Err(invalid_input(format!(
+                "asset {object_path:?} already exists"
+            )));
+        }
+        let (package_path, package_name, asset_name) =
+            split_object_path(object_path).ok_or_else(|| invalid_input("invalid object path"))?;
+        let data = AssetData::builder()
+            .object_path(self.get_name(object_path))
+            .package_path(self.get_name(package_path))
+            .asset_class(self.get_name(asset_class))
+            .package_name(self.get_name(package_name))
+            .asset_name(self.get_name(asset_name))
+            .build()?;
+        self.added.push(data);
         Ok(())
     }
+    /// Finds an added asset, or failing that a base asset, by object path
+    pub fn find_by_object_path(&self, object_path: &str) -> Option<&AssetData> {
+        self.added
+            .iter()
+            .find(|a| self.name(a.object_path) == object_path)
+            .or_else(|| self.base.find_by_object_path(object_path))
+    }
+    /// Object paths of the added assets followed by the base's
+    pub fn object_paths(&self) -> impl Iterator<Item = &str> {
+        let added = self.added.iter().map(|a| self.name(a.object_path));
+        added.chain(self.base.assets().map(|a| a.object_path()))
+    }
 }
 
 #[cfg(test)]
 mod test {
+    use super::*;
+    use crate::test_util::{asset, registry, sample_registry, SAMPLE_REGISTRY_BIN};
+
     #[test]
     fn hash() {
         assert_eq!(cityhasher::hash::<u64>(b"Timestamp"), 0x62701ea6363a9b97);
     }
 
+    #[test]
+    fn asset_data_builder() {
+        let built = AssetData::builder()
+            .object_path(NameIndexFlagged(0, None))
+            .package_path(NameIndexFlagged(1, None))
+            .asset_class(NameIndexFlagged(2, None))
+            .package_name(NameIndexFlagged(3, None))
+            .asset_name(NameIndexFlagged(4, Some(1)))
+            .chunk_ids(vec![7])
+            .build()
+            .unwrap();
+        let literal = AssetData {
+            object_path: NameIndexFlagged(0, None),
+            package_path: NameIndexFlagged(1, None),
+            asset_class: NameIndexFlagged(2, None),
+            package_name: NameIndexFlagged(3, None),
+            asset_name: NameIndexFlagged(4, Some(1)),
+            tags: MapHandle {
+                has_numberless_keys: true,
+                num: 0,
+                pair_begin: 0,
+            },
+            bundle_count: 0,
+            chunk_ids: vec![7],
+            flags: 0,
+        };
+        assert_eq!(built, literal);
+
+        assert!(AssetData::builder()
+            .object_path(NameIndexFlagged(0, None))
+            .build()
+            .is_err());
+    }
+
+    #[test]
+    fn wide_name() {
+        // high bit of the big-endian length marks a UTF-16 name, low bits count code units
+        let fixture = [0x80, 0x04, b'N', 0, 0xe4, 0, b'm', 0, b'e', 0];
+        let mut reader = &fixture[..];
+        let len = reader.read_i16::<BE>().unwrap();
+        assert_eq!(read_string(&mut reader, len).unwrap(), "Näme");
+
+        let ar = registry(&["Foo", "Näme"]);
+        let mut buf = vec![];
+        ar.write(&mut buf).unwrap();
+
+        // guid, version, name count, string bytes, hash version, hashes
+        let lengths = 16 + 4 + 4 + 4 + 8 + 2 * 8;
+        assert_eq!(buf[lengths..lengths + 4], [0x00, 0x03, 0x80, 0x04]);
+        assert_eq!(buf[lengths + 4..lengths + 4 + 3], *b"Foo");
+        assert_eq!(buf[lengths + 4 + 3..lengths + 4 + 3 + 8], fixture[2..]);
+
+        assert_eq!(AssetRegistry::read(&mut &buf[..]).unwrap(), ar);
+    }
+
+    #[test]
+    fn streaming_reader() {
+        /// Non-seekable reader returning at most one byte per call
+        struct Stream<'a>(&'a [u8]);
+        impl Read for Stream<'_> {
+            fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+                let n = buf.len().min(self.0.len()).min(1);
+                buf[..n].copy_from_slice(&self.0[..n]);
+                self.0 = &self.0[n..];
+                Ok(n)
+            }
+        }
+
+        let mut ar = registry(&[]);
+        let data = asset(&mut ar, "/Game/Foo/Bar", "Blueprint");
+        let mut buf = vec![];
+        data.write(&mut buf).unwrap();
+        assert_eq!(AssetData::read(&mut Stream(&buf)).unwrap(), data);
+
+        let ar = AssetRegistry::read(&mut Stream(SAMPLE_REGISTRY_BIN)).unwrap();
+        assert_eq!(ar, sample_registry());
+    }
+
+    #[test]
+    fn byte_order() {
+        let mut ar = registry(&["Foo", "Ab"]);
+        ar.version_int = 0x0102;
+        ar.hash_version = 0x0304;
+        let mut buf = vec![];
+        ar.write(&mut buf).unwrap();
+
+        let mut r = &buf[16..];
+        let mut next = |n: usize| {
+            let (head, tail) = r.split_at(n);
+            r = tail;
+            head.to_vec()
+        };
+        assert_eq!(next(4), [0x02, 0x01, 0, 0], "version");
+        assert_eq!(next(4), [2, 0, 0, 0], "name count");
+        assert_eq!(next(4), [5, 0, 0, 0], "string bytes");
+        assert_eq!(next(8), [0x04, 0x03, 0, 0, 0, 0, 0, 0], "hash version");
+        let hash = cityhasher::hash::<u64>(b"foo");
+        assert_eq!(next(8), hash.to_le_bytes(), "hash");
+        next(8);
+        assert_eq!(next(4), [0, 3, 0, 2], "name lengths");
+        assert_eq!(next(5), *b"FooAb");
+        assert_eq!(next(4), MAGIC_START.to_le_bytes(), "store magic");
+    }
+
+    #[test]
+    fn cached_name_hashes() {
+        let computed = || NAME_HASHES_COMPUTED.with(|c| c.get());
+        let mut ar = registry(&["Foo", "Bar"]);
+
+        let start = computed();
+        let (mut first, mut second) = (vec![], vec![]);
+        ar.write(&mut first).unwrap();
+        ar.write(&mut second).unwrap();
+        assert_eq!(computed(), start);
+        assert_eq!(first, second);
+
+        // only the new name is hashed, once
+        ar.get_name("Baz");
+        ar.get_name("Baz");
+        assert_eq!(computed() - start, 1);
+        let mut third = vec![];
+        ar.write(&mut third).unwrap();
+        assert_eq!(computed() - start, 1);
+        let mut expected_bytes = vec![];
+        registry(&["Foo", "Bar", "Baz"])
+            .write(&mut expected_bytes)
+            .unwrap();
+        assert_eq!(third, expected_bytes);
+    }
+
+    #[test]
+    fn trailing_bytes() {
+        let mut ar = registry(&["Foo"]);
+        ar.trailing = vec![1, 2, 3, 4];
+        let mut buf = vec![];
+        ar.write(&mut buf).unwrap();
+        assert!(buf.ends_with(&[1, 2, 3, 4]));
+
+        let read = AssetRegistry::read_from_slice(&buf).unwrap();
+        assert_eq!(read.trailing, [1, 2, 3, 4]);
+        let mut out = vec![];
+        read.write(&mut out).unwrap();
+        assert_eq!(buf, out);
+    }
+
+    #[test]
+    fn read_from_slice() {
+        let ar = registry(&["Foo"]);
+        let mut buf = vec![];
+        ar.write(&mut buf).unwrap();
+        assert_eq!(AssetRegistry::read_from_slice(&buf).unwrap(), ar);
+    }
+
+    #[test]
+    fn read_names_only() {
+        let ar = registry(&["Foo", "Bar", "/Game/Baz"]);
+        let mut buf = vec![];
+        ar.write(&mut buf).unwrap();
+
+        let mut reader = &buf[..];
+        let names = AssetRegistry::read_names_only(&mut reader).unwrap();
+        assert_eq!(names, ar.names);
+
+        // reader is left at the start of the store
+        assert_eq!(Store::read(&mut reader).unwrap(), ar.store);
+    }
+
+    #[test]
+    fn strict_num_string_bytes() {
+        let ar = registry(&["Foo", "Näme"]);
+        let mut buf = vec![];
+        ar.write(&mut buf).unwrap();
+        let strict = ReadOptions {
+            strict: true,
+            ..Default::default()
+        };
+        AssetRegistry::read_with(&mut &buf[..], strict).unwrap();
+
+        // num_string_bytes follows the version guid, version and name count
+        buf[24..28].copy_from_slice(&3u32.to_le_bytes());
+        let err = AssetRegistry::read_with(&mut &buf[..], strict).unwrap_err();
+        assert_eq!(
+            err.inner().to_string(),
+            "num_string_bytes is 3 but names take 11 bytes"
+        );
+        assert_eq!(AssetRegistry::read_from_slice(&buf).unwrap(), ar);
+    }
+
+    #[test]
+    fn write_to_vec() {
+        let mut ar = registry(&["Foo"]);
+        let foo = asset(&mut ar, "/Game/Foo", "Blueprint");
+        ar.asset_data.push(foo);
+        ar.add_tags(0, [("Key", "Näme")]).unwrap();
+
+        let bytes = ar.write_to_vec().unwrap();
+        assert_eq!(bytes.len() as u64, ar.pending_stats().estimated_bytes);
+        let mut written = vec![];
+        ar.write(&mut written).unwrap();
+        assert_eq!(bytes, written);
+    }
+
+    #[test]
+    fn no_assets() {
+        let ar = registry(&["Foo", "/Game/Bar"]);
+        let bytes = ar.write_to_vec().unwrap();
+        let mut ar = AssetRegistry::read_from_slice(&bytes).unwrap();
+        assert_eq!(ar.names.len(), 2);
+        assert_eq!(ar.write_to_vec().unwrap(), bytes);
+
+        ar.validate().unwrap();
+        assert_eq!(ar.assets().count(), 0);
+        assert!(ar.package_names().is_empty());
+        assert!(ar.tag_key_histogram().is_empty());
+        assert!(ar.find_by_object_path("/Game/Bar.Bar").is_none());
+        ar.retain(|_| true);
+        assert!(!ar.set_chunk_ids("/Game/Bar.Bar", &[1]));
+        assert!(!ar.rename_asset("/Game/Bar.Bar", "/Game/Baz.Baz"));
+    }
+
+    #[test]
+    fn skip_dependencies() {
+        let mut ar = registry(&["Foo"]);
+        ar.dependencies = DependencySection::Parsed(Dependencies {
+            dependencies_size: 8,
+            dependencies: vec![1, 2],
+            package_data_buffer_size: 0,
+        });
+        let mut buf = vec![];
+        ar.write(&mut buf).unwrap();
+
+        let options = ReadOptions {
+            skip_dependencies: true,
+            ..Default::default()
+        };
+        let skipped = AssetRegistry::read_with(&mut &buf[..], options).unwrap();
+        assert!(matches!(skipped.dependencies, DependencySection::Raw(_)));
+
+        let mut out = vec![];
+        skipped.write(&mut out).unwrap();
+        assert_eq!(buf, out);
+    }
+
+    #[test]
+    fn write_with_progress() {
+        let ar = registry(&["Foo", "Bar"]);
+        let mut calls = vec![];
+        let mut buf = vec![];
+        ar.write_with_progress(&mut buf, |written, total| calls.push((written, total)))
+            .unwrap();
+
+        let total = buf.len() as u64;
+        assert_eq!(ar.serialized_size().unwrap(), total);
+        assert_eq!(calls.len(), 4);
+        assert!(calls.windows(2).all(|w| w[0].0 <= w[1].0));
+        assert!(calls.iter().all(|&(_, t)| t == total));
+        assert_eq!(calls.last(), Some(&(total, total)));
+    }
+
+    #[test]
+    fn add_string_tags() {
+        let mut ar = registry(&[]);
+        let foo = asset(&mut ar, "/Game/Foo", "Blueprint");
+        let bar = asset(&mut ar, "/Game/Bar", "Blueprint");
+        ar.asset_data.extend([foo, bar]);
+
+        ar.add_tags(0, [("Ascii", "plain"), ("Empty", "")]).unwrap();
+        ar.add_tags(1, [("Unicode", "Näme"), ("Boundary", "\u{80}")])
+            .unwrap();
+        ar.add_tags(0, [("More", "x")]).unwrap();
+
+        assert_eq!(ar.store.ansi_strings, ["plain", "", "x"]);
+        assert_eq!(ar.store.wide_strings, ["Näme", "\u{80}"]);
+
+        let foo_tags = &ar.asset_data[0].tags;
+        assert_eq!(foo_tags.num, 3);
+        let pairs = &ar.store.pairs[foo_tags.pair_begin as usize..][..3];
+        let keys: Vec<_> = pairs.iter().map(|p| ar.names[p.name].as_str()).collect();
+        assert_eq!(keys, ["Ascii", "Empty", "More"]);
+        assert!(pairs.iter().all(|p| p.type_ == Type::AnsiString));
+
+        let bar_tags = &ar.asset_data[1].tags;
+        let pairs = &ar.store.pairs[bar_tags.pair_begin as usize..][..bar_tags.num as usize];
+        assert!(pairs.iter().all(|p| p.type_ == Type::WideString));
+
+        let forced = ar
+            .push_pair_as("Forced", "plain", Type::WideString)
+            .unwrap();
+        assert_eq!(forced.type_, Type::WideString);
+        assert!(ar.push_pair_as("Bad", "Näme", Type::AnsiString).is_err());
+        assert!(ar.push_pair_as("Bad", "plain", Type::Name).is_err());
+
+        ar.asset_data[1].tags.has_numberless_keys = false;
+        let pairs = ar.store.pairs.len();
+        assert!(ar.add_tags(1, [("More", "x")]).is_err());
+        assert_eq!(ar.store.pairs.len(), pairs);
+        assert_eq!(ar.asset_data[1].tags.key_type(), Type::Name);
+    }
+
+    #[test]
+    fn tag_value() {
+        let mut ar = registry(&[]);
+        let foo = asset(&mut ar, "/Game/Foo", "Blueprint");
+        ar.asset_data.push(foo);
+        ar.add_tags(0, [("ParentClass", "Actor")]).unwrap();
+
+        let foo = &ar.asset_data[0];
+        let expected = Some(TagValue::AnsiString("Actor".to_string()));
+        assert_eq!(ar.tag_value(foo, "ParentClass"), expected);
+        assert_eq!(ar.tag_value(foo, "parentclass"), expected);
+        assert_eq!(ar.tag_value(foo, "Missing"), None);
+
+        ar.asset_data[0].tags.has_numberless_keys = false;
+        assert_eq!(ar.tag_value(&ar.asset_data[0], "ParentClass"), None);
+        ar.asset_data[0].tags.has_numberless_keys = true;
+        ar.asset_data[0].tags.pair_begin = 1;
+        assert_eq!(ar.tag_value(&ar.asset_data[0], "ParentClass"), None);
+    }
+
+    #[test]
+    fn pending_stats() {
+        let mut ar = registry(&[]);
+        let foo = asset(&mut ar, "/Game/Foo", "Blueprint");
+        let bar = asset(&mut ar, "/Game/Bar", "Blueprint");
+        ar.asset_data.extend([foo, bar]);
+        ar.asset_data[1].chunk_ids = vec![1, 2];
+        ar.add_tags(0, [("Ascii", "plain"), ("Unicode", "Näme")])
+            .unwrap();
+
+        let stats = ar.pending_stats();
+        assert_eq!(stats.asset_count, 2);
+        assert_eq!(stats.name_count, ar.names.len());
+        assert_eq!(stats.pair_count, 2);
+        assert_eq!(stats.estimated_bytes, ar.serialized_size().unwrap());
+    }
+
+    #[test]
+    fn store_stats() {
+        let mut ar = registry(&[]);
+        let foo = asset(&mut ar, "/Game/Foo", "Blueprint");
+        ar.asset_data.push(foo);
+        ar.add_tags(0, [("Ascii", "plain"), ("Unicode", "N\u{e4}me")])
+            .unwrap();
+        // a pair no asset owns and a string no pair points at
+        ar.push_pair("Orphan", "dangling");
+        ar.store.ansi_strings.push("dead".to_string());
+
+        let stats = ar.store_stats();
+        assert_eq!(
+            stats.ansi_strings,
+            VectorStats {
+                count: 3,
+                bytes: (4 + 6) + (4 + 9) + (4 + 5),
+                referenced: 2,
+            }
+        );
+        assert_eq!(
+            stats.wide_strings,
+            VectorStats {
+                count: 1,
+                bytes: 4 + 2 * 5,
+                referenced: 1,
+            }
+        );
+        assert_eq!(
+            stats.pairs,
+            VectorStats {
+                count: 3,
+                bytes: 24,
+                referenced: 2,
+            }
+        );
+        assert_eq!(stats.texts, VectorStats::default());
+        assert_eq!(stats.names, VectorStats::default());
+    }
+
+    #[test]
+    fn write_diff_regions() {
+        let apply = |patches: &[(usize, Vec<u8>)]| {
+            let mut bytes = SAMPLE_REGISTRY_BIN.to_vec();
+            for (offset, patch) in patches {
+                bytes[*offset..offset + patch.len()].copy_from_slice(patch);
+            }
+            bytes
+        };
+
+        let mut ar = sample_registry();
+        assert_eq!(
+            ar.write_diff_regions(SAMPLE_REGISTRY_BIN).unwrap(),
+            RegistryUpdate::Patch(vec![])
+        );
+
+        ar.asset_data[1].flags = 0x10;
+        let RegistryUpdate::Patch(patches) = ar.write_diff_regions(SAMPLE_REGISTRY_BIN).unwrap()
+        else {
+            panic!("flags change needs a rewrite");
+        };
+        assert_eq!(patches.len(), 1);
+        assert_eq!(patches[0].1, 0x10u32.to_le_bytes());
+        assert_eq!(apply(&patches), ar.write_to_vec().unwrap());
+
+        ar.asset_data[2].chunk_ids = vec![7];
+        let RegistryUpdate::Patch(patches) = ar.write_diff_regions(SAMPLE_REGISTRY_BIN).unwrap()
+        else {
+            panic!("chunk id change needs a rewrite");
+        };
+        assert_eq!(patches.len(), 2);
+        assert_eq!(apply(&patches), ar.write_to_vec().unwrap());
+
+        ar.asset_data[2].chunk_ids = vec![1, 2];
+        assert_eq!(
+            ar.write_diff_regions(SAMPLE_REGISTRY_BIN).unwrap(),
+            RegistryUpdate::Rewrite(ar.write_to_vec().unwrap())
+        );
+
+        let mut ar = sample_registry();
+        ar.names = Names::new(ar.names.iter().rev().map(String::from).collect());
+        assert!(matches!(
+            ar.write_diff_regions(SAMPLE_REGISTRY_BIN).unwrap(),
+            RegistryUpdate::Rewrite(_)
+        ));
+    }
+
+    #[test]
+    fn summary() {
+        let mut ar = sample_registry();
+        ar.version = ASSET_REGISTRY_VERSION_GUID;
+        ar.version_int = 8;
+        assert_eq!(ar.asset_count(), 3);
+
+        let summary = ar.summary();
+        assert_eq!(summary.engine_hint, Some(EngineVersion::VER_UE4_27));
+        assert_eq!(summary.name_count, ar.names.len());
+        assert_eq!(summary.store.pairs.count, 3);
+        let debug = format!("{summary:?}");
+        assert!(debug.contains("asset_count: 3"), "{debug}");
+        assert!(!debug.contains("/Game/Foo"), "{debug}");
+    }
+
+    #[test]
+    fn wide_string_offsets() {
+        let mut store = registry(&[]).store;
+        store.wide_strings = vec!["Näme".to_string(), "\u{1F600}x".to_string()];
+        let mut buf = vec![];
+        store.write(&mut buf).unwrap();
+
+        let u32_at = |i: usize| u32::from_le_bytes(buf[i..i + 4].try_into().unwrap());
+        // offsets and total size count UTF-16 code units, so the emoji is two
+        assert_eq!(u32_at(4 * 9), 5 + 4);
+        assert_eq!([u32_at(4 * 13), u32_at(4 * 14)], [0, 5]);
+
+        assert_eq!(Store::read(&mut &buf[..]).unwrap(), store);
+    }
+
+    #[test]
+    fn group_pairs_by_type() {
+        for (i, type_) in Type::ALL.into_iter().enumerate() {
+            assert_eq!(Type::try_from(i as u32).unwrap(), type_);
+        }
+
+        let mut ar = registry(&[]);
+        ar.push_pair("A", "a");
+        ar.push_pair("B", "Näme");
+        ar.push_pair("C", "c");
+
+        let mut groups = std::collections::HashMap::<Type, Vec<&Pair>>::new();
+        for pair in &ar.store.pairs {
+            groups.entry(pair.type_).or_default().push(pair);
+        }
+        assert_eq!(groups[&Type::AnsiString].len(), 2);
+        assert_eq!(groups[&Type::WideString].len(), 1);
+        assert!(!groups.contains_key(&Type::Name));
+    }
+
+    #[test]
+    fn set_chunk_ids() {
+        let mut ar = registry(&[]);
+        let blueprint = asset(&mut ar, "/Game/BP", "Blueprint");
+        let mut class = asset(&mut ar, "/Game/BP", "BlueprintGeneratedClass");
+        class.object_path = ar.get_name("/Game/BP.BP_C");
+        class.asset_name = ar.get_name("BP_C");
+        let other = asset(&mut ar, "/Game/Other", "Blueprint");
+        ar.asset_data.extend([blueprint, class, other]);
+
+        assert!(ar.set_chunk_ids("/Game/BP.BP", &[3]));
+        assert_eq!(ar.asset_data[0].chunk_ids, [3]);
+        assert_eq!(ar.asset_data[1].chunk_ids, [3]);
+
+        assert!(ar.set_chunk_ids("/Game/BP.BP_C", &[1, 2]));
+        assert_eq!(ar.asset_data[0].chunk_ids, [1, 2]);
+        assert_eq!(ar.asset_data[1].chunk_ids, [1, 2]);
+        assert!(ar.asset_data[2].chunk_ids.is_empty());
+
+        assert!(!ar.set_chunk_ids("/Game/Missing.Missing", &[1]));
+    }
+
+    #[test]
+    fn package_names() {
+        let mut ar = registry(&[]);
+        let blueprint = asset(&mut ar, "/Game/BP", "Blueprint");
+        let mut class = asset(&mut ar, "/Game/BP", "BlueprintGeneratedClass");
+        class.object_path = ar.get_name("/Game/BP.BP_C");
+        let other = asset(&mut ar, "/Game/Other", "Blueprint");
+        ar.asset_data.extend([blueprint, class, other]);
+
+        let names: Vec<_> = ar.package_names().into_iter().collect();
+        assert_eq!(names, ["/Game/BP", "/Game/Other"]);
+    }
+
+    #[test]
+    fn classes() {
+        let mut ar = sample_registry();
+        let extra = asset(&mut ar, "/Game/Foo/Baz", "Blueprint");
+        ar.asset_data.push(extra);
+
+        let classes: Vec<_> = ar.classes().into_iter().collect();
+        assert_eq!(
+            classes,
+            ["Blueprint", "BlueprintGeneratedClass", "Texture2D"]
+        );
+        assert_eq!(
+            ar.count_by_class().into_iter().collect::<Vec<_>>(),
+            [
+                ("Blueprint".to_string(), 2),
+                ("BlueprintGeneratedClass".to_string(), 1),
+                ("Texture2D".to_string(), 1),
+            ]
+        );
+    }
+
+    #[test]
+    fn dedup_values() {
+        let mut ar = sample_registry();
+        let tags = [("Format", "BC1"), ("DisplayName", "Fels\u{f6}")];
+        let extra = asset(&mut ar, "/Game/Textures/T_Sand", "Texture2D");
+        ar.asset_data.push(extra);
+        ar.add_tags(3, tags).unwrap();
+        ar.add_tags(0, [("Format", "BC1")]).unwrap();
+        let handles: Vec<_> = ar
+            .asset_data
+            .iter()
+            .map(|a| (a.tags.pair_begin, a.tags.num))
+            .collect();
+        let resolved = |ar: &AssetRegistry| -> Vec<Vec<(String, TagValue)>> {
+            ar.asset_data
+                .iter()
+                .map(|a| {
+                    ar.resolve(a)
+                        .tags()
+                        .map(|(k, v)| (k.to_string(), v))
+                        .collect()
+                })
+                .collect()
+        };
+        let before = resolved(&ar);
+
+        ar.store.dedup_values();
+
+        assert_eq!(ar.store.ansi_strings, ["/Script/Engine.Actor", "BC1"]);
+        assert_eq!(ar.store.wide_strings, ["Fels\u{f6}"]);
+        assert_eq!(resolved(&ar), before);
+        assert!(ar
+            .asset_data
+            .iter()
+            .map(|a| (a.tags.pair_begin, a.tags.num))
+            .eq(handles));
+    }
+
+    #[cfg(feature = "compress")]
+    #[test]
+    fn compressed() {
+        for codec in [Codec::Gzip, Codec::Zstd] {
+            let mut compressed = vec![];
+            sample_registry()
+                .write_compressed(&mut compressed, codec)
+                .unwrap();
+            assert_ne!(compressed, crate::test_util::SAMPLE_REGISTRY_BIN);
+            let ar = AssetRegistry::read_compressed(&compressed[..], codec).unwrap();
+            assert_eq!(ar, sample_registry());
+        }
+    }
+
+    #[test]
+    fn populate_bundle_count() {
+        let mut ar = registry(&[]);
+        let fields = super::registry_fields_for("/Game/Data/DA_Loadout", "DA_Loadout", "DataAsset");
+        ar.insert_populated(fields.unwrap(), 2).unwrap();
+        let fields = super::registry_fields_for("/Game/Data/DA_Other", "DA_Other", "DataAsset");
+        ar.insert_populated(fields.unwrap(), 0).unwrap();
+
+        let ar = AssetRegistry::read_from_slice(&ar.write_to_vec().unwrap()).unwrap();
+        let counts: Vec<_> = ar.asset_data.iter().map(|a| a.bundle_count).collect();
+        assert_eq!(counts, [2, 0]);
+    }
+
+    #[test]
+    fn populate_game_path() {
+        let asset = crate::test_util::generated_class_asset();
+        let mut by_pak = registry(&[]);
+        by_pak.populate("FSD/Content/Foo/Bar", &asset).unwrap();
+        let mut by_game = registry(&[]);
+        by_game.populate_game_path("/Game/Foo/Bar", &asset).unwrap();
+        assert_eq!(by_game, by_pak);
+
+        let paths: Vec<_> = by_game.assets().map(|a| a.object_path()).collect();
+        assert_eq!(paths, ["/Game/Foo/Bar.Bar_C", "/Game/Foo/Bar.Bar"]);
+    }
+
+    #[test]
+    fn preview_populate() {
+        let mut ar = registry(&[]);
+        let fields =
+            super::registry_fields_for("/Game/Foo/Bar", "Bar_C", "BlueprintGeneratedClass")
+                .unwrap();
+
+        let names = ar.names.len();
+        let preview = ar.preview_populated(fields.clone());
+        assert_eq!(ar.names.len(), names);
+        assert_eq!(preview.len(), 2);
+        assert_eq!(preview[1].object_path, "/Game/Foo/Bar.Bar");
+        assert_eq!(preview[1].asset_class, "Blueprint");
+
+        ar.insert_populated(fields.clone(), 0).unwrap();
+        let added: Vec<_> = ar
+            .assets()
+            .map(|a| RegistryFields {
+                object_path: a.object_path().to_string(),
+                package_path: a.package_path().to_string(),
+                asset_class: a.asset_class().to_string(),
+                package_name: a.package_name().to_string(),
+                asset_name: a.asset_name().to_string(),
+            })
+            .collect();
+        assert_eq!(added, preview);
+
+        assert!(ar.preview_populated(fields).is_empty());
+    }
+
+    #[test]
+    fn diff_pak_files() {
+        let ar = sample_registry();
+        let (dangling, unregistered) = ar.diff_pak_files([
+            "FSD/Content/foo/bar.uasset",
+            "FSD/Content/Foo/Bar.uexp",
+            "FSD/Content/Maps/Level.umap",
+            "FSD/Content/Textures/T_Rock.ubulk",
+        ]);
+        assert_eq!(dangling, ["/Game/Textures/T_Rock"]);
+        assert_eq!(unregistered, ["/Game/Maps/Level"]);
+    }
+
+    #[test]
+    fn default_store() {
+        let mut bytes = vec![];
+        Store::default().write(&mut bytes).unwrap();
+        assert_eq!(bytes[..4], MAGIC_START.to_le_bytes());
+        assert_eq!(bytes[bytes.len() - 4..], MAGIC_END.to_le_bytes());
+        assert_eq!(Store::read(&mut &bytes[..]).unwrap(), Store::default());
+
+        let mut bytes = vec![];
+        Dependencies::default().write(&mut bytes).unwrap();
+        assert_eq!(
+            Dependencies::read(&mut &bytes[..]).unwrap(),
+            Dependencies::default()
+        );
+    }
+
+    #[test]
+    fn to_csv() {
+        let mut ar = sample_registry();
+        let quoted = asset(&mut ar, "/Game/Odd/Comma,\"Name\"", "Blueprint");
+        ar.asset_data.push(quoted);
+
+        let mut csv = vec![];
+        ar.to_csv(&mut csv).unwrap();
+        let csv = String::from_utf8(csv).unwrap();
+        let rows: Vec<_> = csv.lines().collect();
+        assert_eq!(rows.len(), 5);
+        assert_eq!(
+            rows[0],
+            "object_path,package_name,asset_class,chunk_ids,flags"
+        );
+        assert_eq!(rows[1], "/Game/Foo/Bar.Bar,/Game/Foo/Bar,Blueprint,0;3,0");
+        assert_eq!(
+            rows[3],
+            "/Game/Textures/T_Rock.T_Rock,/Game/Textures/T_Rock,Texture2D,1,0"
+        );
+        assert_eq!(
+            rows[4],
+            r#""/Game/Odd/Comma,""Name"".Comma,""Name""","/Game/Odd/Comma,""Name""",Blueprint,,0"#
+        );
+    }
+
+    #[test]
+    fn contains_package() {
+        let ar = sample_registry();
+        assert!(ar.contains_package("/Game/Foo/Bar"));
+        assert!(ar.contains_package("/game/textures/t_rock"));
+        // a name that exists but is not a package name
+        assert!(!ar.contains_package("/Game/Foo"));
+        assert!(!ar.contains_package("/Game/Missing"));
+    }
+
+    #[test]
+    fn engine_hint() {
+        let mut ar = registry(&[]);
+        assert_eq!(ar.engine_hint(), None);
+
+        ar.version = ASSET_REGISTRY_VERSION_GUID;
+        ar.version_int = 8;
+        assert_eq!(ar.engine_hint(), Some(EngineVersion::VER_UE4_27));
+
+        ar.version_int = 16;
+        assert_eq!(ar.engine_hint(), None);
+    }
+
+    #[test]
+    fn apply_patch() {
+        let mut ar = sample_registry();
+        let patch = RegistryPatch {
+            add: vec![AssetSpec {
+                object_path: "/Game/Data/DA_Loot.DA_Loot".to_string(),
+                asset_class: "DataAsset".to_string(),
+            }],
+            remove: vec!["/Game/Foo/Bar.Bar_C".to_string()],
+            set_chunks: vec![("/Game/Data/DA_Loot.DA_Loot".to_string(), vec![2])],
+            set_tags: vec![(
+                "/Game/Textures/T_Rock.T_Rock".to_string(),
+                vec![
+                    (
+                        "Format".to_string(),
+                        TagValue::AnsiString("BC7".to_string()),
+                    ),
+                    (
+                        "Tier".to_string(),
+                        TagValue::WideString("\u{e9}pic".to_string()),
+                    ),
+                ],
+            )],
+        };
+
+        // nothing changes if any part of the patch is invalid
+        let mut invalid = patch.clone();
+        invalid.remove.push("/Game/Missing.Missing".to_string());
+        assert!(ar.apply_patch(invalid).is_err());
+        assert_eq!(ar, sample_registry());
+
+        // a tag map outside the store, or one that would overflow, is rejected up front too
+        let out_of_range = || {
+            let mut ar = sample_registry();
+            ar.asset_data[2].tags.num = 9;
+            ar
+        };
+        let mut bad = out_of_range();
+        assert!(matches!(
+            bad.apply_patch(patch.clone()),
+            Err(UAssetError::InvalidInput(_))
+        ));
+        assert_eq!(bad, out_of_range());
+        let mut overflow = patch.clone();
+        overflow.set_tags[0].1 = (0..u16::MAX - 1)
+            .map(|i| (format!("K{i}"), TagValue::AnsiString(String::new())))
+            .collect();
+        assert!(ar.apply_patch(overflow).is_err());
+        assert_eq!(ar, sample_registry());
+
+        ar.apply_patch(patch).unwrap();
+        ar.validate().unwrap();
+        let paths: Vec<_> = ar.assets().map(|a| a.object_path()).collect();
+        assert_eq!(
+            paths,
+            [
+                "/Game/Foo/Bar.Bar",
+                "/Game/Textures/T_Rock.T_Rock",
+                "/Game/Data/DA_Loot.DA_Loot"
+            ]
+        );
+        let loot = ar.resolve(&ar.asset_data[2]);
+        assert_eq!(loot.package_path(), "/Game/Data");
+        assert_eq!(loot.asset_name(), "DA_Loot");
+        assert_eq!(loot.data.chunk_ids, [2]);
+
+        let tags: Vec<_> = ar.resolve(&ar.asset_data[1]).tags().collect();
+        assert_eq!(
+            tags,
+            [
+                (
+                    "DisplayName",
+                    TagValue::WideString("Fels\u{f6}".to_string())
+                ),
+                ("Format", TagValue::AnsiString("BC7".to_string())),
+                ("Tier", TagValue::WideString("\u{e9}pic".to_string())),
+            ]
+        );
+        assert_eq!(
+            ar.resolve(&ar.asset_data[0]).tag_value("ParentClass"),
+            Some(TagValue::SoftObjectPath("/Script/Engine.Actor".to_string()))
+        );
+    }
+
+    #[test]
+    fn numbered_maps_untouched() {
+        let numbered = || MapHandle {
+            has_numberless_keys: false,
+            num: 2,
+            pair_begin: 2,
+        };
+        let mut ar = sample_registry();
+        ar.asset_data[1].tags = numbered();
+        ar.strip_tags(&["Format"]);
+        assert_eq!(ar.asset_data[1].tags, numbered());
+        assert_eq!(
+            ar.resolve(&ar.asset_data[0]).tag_value("ParentClass"),
+            Some(TagValue::SoftObjectPath("/Script/Engine.Actor".to_string()))
+        );
+
+        let mut ar = sample_registry();
+        ar.asset_data[1].tags = numbered();
+        let patch = RegistryPatch {
+            set_tags: vec![(
+                "/Game/Textures/T_Rock.T_Rock".to_string(),
+                vec![("Tier".to_string(), TagValue::AnsiString("1".to_string()))],
+            )],
+            ..Default::default()
+        };
+        ar.apply_patch(patch).unwrap();
+        assert_eq!(ar.asset_data[1].tags, numbered());
+        assert_eq!(
+            ar.resolve(&ar.asset_data[2]).tag_value("Tier"),
+            Some(TagValue::AnsiString("1".to_string()))
+        );
+    }
+
+    #[test]
+    fn strip_tags_out_of_range() {
+        let mut ar = sample_registry();
+        let out_of_range = || MapHandle {
+            has_numberless_keys: true,
+            num: 2,
+            pair_begin: 2,
+        };
+        ar.asset_data[1].tags = out_of_range();
+        ar.strip_tags(&["Format"]);
+        assert_eq!(ar.asset_data[1].tags, out_of_range());
+        let tags: Vec<_> = ar.resolve(&ar.asset_data[2]).tags().collect();
+        assert_eq!(
+            tags,
+            [(
+                "DisplayName",
+                TagValue::WideString("Fels\u{f6}".to_string())
+            )]
+        );
+    }
+
+    #[test]
+    fn chunk_map() {
+        let mut ar = sample_registry();
+        let unchunked = asset(&mut ar, "/Game/Loose", "Blueprint");
+        ar.asset_data.push(unchunked);
+
+        let map = ar.chunk_map();
+        assert_eq!(map.keys().copied().collect::<Vec<_>>(), [0, 1, 3]);
+        assert_eq!(map[&0], ["/Game/Foo/Bar.Bar", "/Game/Foo/Bar.Bar_C"]);
+        assert_eq!(map[&1], ["/Game/Textures/T_Rock.T_Rock"]);
+        assert_eq!(map[&3], map[&0]);
+    }
+
+    #[test]
+    fn localized_texts() {
+        assert_eq!(
+            parse_localized_text(r#"NSLOCTEXT("UI", "Title", "Say \"hi\"")"#),
+            Some(("UI".into(), "Title".into(), "Say \"hi\"".into()))
+        );
+        assert_eq!(parse_localized_text(r#"INVTEXT("Plain")"#), None);
+        assert_eq!(parse_localized_text(r#"NSLOCTEXT("UI", "Title")"#), None);
+
+        let mut ar = registry(&[]);
+        let foo = asset(&mut ar, "/Game/Foo", "Blueprint");
+        let bar = asset(&mut ar, "/Game/Bar", "Blueprint");
+        ar.asset_data.extend([foo, bar]);
+        let text = |ar: &mut AssetRegistry, key: &str, value: &str| {
+            ar.push_pair_as(key, value, Type::LocalizedText).unwrap();
+        };
+        text(&mut ar, "Name", r#"NSLOCTEXT("Items", "Rock", "Rock")"#);
+        text(
+            &mut ar,
+            "Tooltip",
+            r#"NSLOCTEXT("Items", "Rock_Tip", "A rock")"#,
+        );
+        text(&mut ar, "Name", r#"NSLOCTEXT("Items", "Rock", "Rock")"#);
+        text(&mut ar, "Note", r#"INVTEXT("untranslated")"#);
+        ar.asset_data[0].tags = MapHandle {
+            has_numberless_keys: true,
+            num: 2,
+            pair_begin: 0,
+        };
+        ar.asset_data[1].tags = MapHandle {
+            has_numberless_keys: true,
+            num: 2,
+            pair_begin: 2,
+        };
+        ar.validate().unwrap();
+
+        assert_eq!(
+            ar.localized_texts(),
+            [
+                ("Items".into(), "Rock".into(), "Rock".into()),
+                ("Items".into(), "Rock_Tip".into(), "A rock".into()),
+            ]
+        );
+
+        // numbered keys and out of range maps are skipped
+        ar.asset_data[0].tags.has_numberless_keys = false;
+        ar.asset_data[1].tags.num = 3;
+        assert_eq!(ar.localized_texts(), []);
+        ar.asset_data[1].tags.num = 2;
+        assert_eq!(
+            ar.localized_texts(),
+            [("Items".into(), "Rock".into(), "Rock".into())]
+        );
+    }
+
+    #[test]
+    fn iter_pairs() {
+        let mut ar = sample_registry();
+        // not attached to any asset
+        ar.push_pair_as(
+            "Orphan",
+            "NSLOCTEXT(\"\", \"k\", \"v\")",
+            Type::LocalizedText,
+        )
+        .unwrap();
+
+        let pairs: Vec<_> = ar.iter_pairs().collect();
+        assert_eq!(pairs.len(), ar.store.pairs.len());
+        assert_eq!(
+            pairs,
+            [
+                (
+                    "ParentClass",
+                    TagValue::SoftObjectPath("/Script/Engine.Actor".into())
+                ),
+                ("Format", TagValue::AnsiString("BC1".into())),
+                ("DisplayName", TagValue::WideString("Fels\u{f6}".into())),
+                (
+                    "Orphan",
+                    TagValue::LocalizedText("NSLOCTEXT(\"\", \"k\", \"v\")".into())
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn content_hash() {
+        let build = |names: &[&str]| {
+            let mut ar = registry(names);
+            let foo = asset(&mut ar, "/Game/Foo", "Blueprint");
+            let bar = asset(&mut ar, "/Game/Bar", "Blueprint");
+            ar.asset_data.extend([foo, bar]);
+            ar.add_tags(1, [("Key", "Value")]).unwrap();
+            ar
+        };
+        let a = build(&[]);
+        let b = build(&["Value", "/Game/Bar", "Key", "Blueprint"]);
+
+        let (mut a_bytes, mut b_bytes) = (vec![], vec![]);
+        a.write(&mut a_bytes).unwrap();
+        b.write(&mut b_bytes).unwrap();
+        assert_ne!(a_bytes, b_bytes);
+        assert_eq!(a.content_hash(), b.content_hash());
+
+        let mut c = build(&[]);
+        c.asset_data[0].flags = 1;
+        assert_ne!(a.content_hash(), c.content_hash());
+
+        let mut d = build(&[]);
+        d.asset_data[1].tags.has_numberless_keys = false;
+        assert_ne!(a.content_hash(), d.content_hash());
+        d.asset_data[1].tags.has_numberless_keys = true;
+        d.asset_data[1].tags.num = 2;
+        assert_ne!(a.content_hash(), d.content_hash());
+    }
+
+    #[test]
+    fn validate_map_handles() {
+        let mut ar = registry(&[]);
+        let foo = asset(&mut ar, "/Game/Foo", "Blueprint");
+        ar.asset_data.push(foo);
+        ar.add_tags(0, [("Key", "Value")]).unwrap();
+        ar.validate().unwrap();
+
+        ar.asset_data[0].tags.has_numberless_keys = false;
+        assert_eq!(ar.asset_data[0].tags.key_type(), Type::Name);
+        assert_eq!(
+            ar.store.key_type(&ar.asset_data[0].tags),
+            Some(Type::NumberlessName)
+        );
+        assert!(matches!(ar.validate(), Err(UAssetError::InvalidInput(_))));
+
+        // numbered maps are valid when they fit the numbered pairs
+        ar.store.pair_count = 1;
+        assert_eq!(ar.store.key_type(&ar.asset_data[0].tags), Some(Type::Name));
+        ar.validate().unwrap();
+        ar.asset_data[0].tags.num = 2;
+        assert_eq!(ar.store.key_type(&ar.asset_data[0].tags), None);
+        assert!(matches!(
+            ar.validate(),
+            Err(UAssetError::IndexOutOfRange(_))
+        ));
+        ar.store.pair_count = 0;
+
+        ar.asset_data[0].tags.has_numberless_keys = true;
+        ar.asset_data[0].tags.num = 2;
+        assert!(matches!(
+            ar.validate(),
+            Err(UAssetError::IndexOutOfRange(_))
+        ));
+    }
+
+    #[test]
+    fn write_checks_indices() {
+        let mut ar = sample_registry();
+        ar.write_to_vec().unwrap();
+
+        let name_count = ar.names.len() as u32;
+        ar.asset_data[1].asset_class = NameIndexFlagged(name_count, None);
+        let err = ar.write_to_vec().unwrap_err();
+        assert!(matches!(err, UAssetError::IndexOutOfRange(_)), "{err}");
+        assert!(err.to_string().contains("asset 1"), "{err}");
+        // unchecked writes still go through
+        let mut buf = vec![];
+        ar.write_unchecked(&mut buf).unwrap();
+
+        let mut ar = sample_registry();
+        ar.store.pairs[1].index = 99;
+        let err = ar.write_to_vec().unwrap_err();
+        assert!(err.to_string().contains("pair 1"), "{err}");
+        assert!(ar.validate().is_err());
+
+        let mut ar = sample_registry();
+        ar.store
+            .nbl_names
+            .push(NameIndexFlagged(u32::MAX >> 1, None));
+        assert!(ar.write_to_vec().is_err());
+    }
+
+    #[test]
+    fn error_variants() {
+        let ar = registry(&["Foo"]);
+        let mut buf = vec![];
+        ar.write(&mut buf).unwrap();
+
+        let mut reader = &buf[..];
+        AssetRegistry::read_names_only(&mut reader).unwrap();
+        let store_offset = buf.len() - reader.len();
+
+        let read = |bytes: &[u8]| AssetRegistry::read_from_slice(bytes).unwrap_err();
+
+        let truncated = read(&buf[..buf.len() - 2]);
+        assert!(matches!(truncated.inner(), UAssetError::Truncated));
+
+        let mut bad_magic = buf.clone();
+        bad_magic[store_offset] ^= 0xff;
+        assert!(matches!(
+            read(&bad_magic).inner(),
+            UAssetError::BadMagic {
+                expected: MAGIC_START,
+                ..
+            }
+        ));
+
+        let mut bad_name = buf.clone();
+        bad_name[store_offset - 1] = 0xff;
+        assert!(matches!(read(&bad_name).inner(), UAssetError::Utf8));
+
+        assert!(matches!(
+            Type::try_from(7),
+            Err(UAssetError::InvalidType(7))
+        ));
+
+        assert!(matches!(
+            AssetData::builder().build(),
+            Err(UAssetError::InvalidInput(msg)) if msg == "missing object_path"
+        ));
+        let mut ar = registry(&[]);
+        let patch = RegistryPatch {
+            remove: vec!["/Game/Missing.Missing".to_string()],
+            ..Default::default()
+        };
+        assert!(matches!(
+            ar.apply_patch(patch),
+            Err(UAssetError::InvalidInput(_))
+        ));
+        assert!(matches!(
+            ar.push_pair_as("Key", "Näme", Type::AnsiString),
+            Err(UAssetError::InvalidInput(_))
+        ));
+    }
+
+    #[test]
+    fn error_offset() {
+        let mut ar = registry(&[]);
+        let foo = asset(&mut ar, "/Game/Foo", "Blueprint");
+        ar.asset_data.push(foo);
+        ar.add_tags(0, [("Key", "Value")]).unwrap();
+        let mut buf = ar.write_to_vec().unwrap();
+
+        // the only pair sits right before the store's end marker
+        let store_end = buf
+            .windows(4)
+            .position(|w| w == MAGIC_END.to_le_bytes())
+            .unwrap();
+        buf[store_end - 4] |= 7;
+
+        let err = AssetRegistry::read_from_slice(&buf).unwrap_err();
+        assert!(matches!(err.inner(), UAssetError::InvalidType(7)));
+        assert_eq!(err.offset(), Some(store_end as u64));
+        assert_eq!(
+            err.to_string(),
+            format!("invalid AssetRegistry type: 7 at offset {store_end:#x}")
+        );
+    }
+
+    #[test]
+    fn tags_json() {
+        let mut ar = registry(&[]);
+        let foo = asset(&mut ar, "/Game/Foo", "Blueprint");
+        ar.asset_data.push(foo);
+
+        let name = ar.get_name("Actor");
+        ar.store.nbl_names.push(name);
+        let export_path = ExportPath {
+            object_path: ar.get_name("Bar_C"),
+            package_path: ar.get_name("/Game/Bar"),
+            asset_class: ar.get_name("BlueprintGeneratedClass"),
+        };
+        ar.store.nbl_export_paths.push(export_path);
+        for (key, type_) in [
+            ("ParentClass", Type::NumberlessName),
+            ("GeneratedClass", Type::NumberlessExportPath),
+        ] {
+            let name = NameIndex(ar.get_name(key).0);
+            ar.store.pairs.push(Pair {
+                name,
+                type_,
+                index: 0,
+            });
+        }
+        ar.asset_data[0].tags = MapHandle {
+            has_numberless_keys: true,
+            num: 2,
+            pair_begin: 0,
+        };
+
+        let json = ar.resolve(&ar.asset_data[0]).tags_json();
+        assert_eq!(
+            serde_json::Value::Object(json),
+            serde_json::json!({
+                "ParentClass": "Actor",
+                "GeneratedClass": {
+                    "package": "/Game/Bar",
+                    "object": "Bar_C",
+                    "class": "BlueprintGeneratedClass",
+                },
+            })
+        );
+
+        ar.asset_data[0].tags.num = 3;
+        assert!(ar.resolve(&ar.asset_data[0]).tags_json().is_empty());
+        ar.asset_data[0].tags.num = 2;
+        ar.asset_data[0].tags.has_numberless_keys = false;
+        assert_eq!(ar.resolve(&ar.asset_data[0]).tags().count(), 0);
+    }
+
+    #[test]
+    fn rename_asset() {
+        let mut ar = registry(&[]);
+        let foo = asset(&mut ar, "/Game/Foo", "Blueprint");
+        let foo_c = AssetData::builder()
+            .object_path(ar.get_name("/Game/Foo.Foo_C"))
+            .package_path(foo.package_path)
+            .asset_class(ar.get_name("BlueprintGeneratedClass"))
+            .package_name(foo.package_name)
+            .asset_name(ar.get_name("Foo_C"))
+            .build()
+            .unwrap();
+        let other = asset(&mut ar, "/Game/Other", "Blueprint");
+        ar.asset_data.extend([foo, foo_c, other]);
+
+        assert!(!ar.rename_asset("/Game/Foo.Foo", "/Game/Other.Other"));
+        assert!(!ar.rename_asset("/Game/Missing.Missing", "/Game/New.New"));
+
+        assert!(ar.rename_asset("/Game/Foo.Foo_C", "/Game/Mod/Bar.Bar_C"));
+        assert!(ar.find_by_object_path("/Game/Foo.Foo").is_none());
+        assert!(ar.find_by_object_path("/Game/Foo.Foo_C").is_none());
+
+        let bar = ar.resolve(ar.find_by_object_path("/Game/Mod/Bar.Bar").unwrap());
+        assert_eq!(bar.package_path(), "/Game/Mod");
+        assert_eq!(bar.package_name(), "/Game/Mod/Bar");
+        assert_eq!(bar.asset_name(), "Bar");
+        assert_eq!(bar.asset_class(), "Blueprint");
+        let bar_c = ar.resolve(ar.find_by_object_path("/Game/Mod/Bar.Bar_C").unwrap());
+        assert_eq!(bar_c.asset_name(), "Bar_C");
+        assert_eq!(bar_c.asset_class(), "BlueprintGeneratedClass");
+    }
+
+    #[test]
+    fn tag_key_histogram() {
+        let mut ar = registry(&[]);
+        for package in ["/Game/A", "/Game/B", "/Game/C"] {
+            let a = asset(&mut ar, package, "Blueprint");
+            ar.asset_data.push(a);
+        }
+        ar.add_tags(0, [("ParentClass", "Actor"), ("NativeClass", "Actor")])
+            .unwrap();
+        ar.add_tags(1, [("ParentClass", "Pawn")]).unwrap();
+        ar.add_tags(2, [("Numbered", "x")]).unwrap();
+        ar.asset_data[2].tags.has_numberless_keys = false;
+        // points past the end of the store
+        ar.asset_data[1].tags.num = 9;
+
+        let histogram = ar.tag_key_histogram();
+        assert_eq!(
+            histogram.into_iter().collect::<Vec<_>>(),
+            [
+                ("NativeClass".to_string(), 1),
+                ("ParentClass".to_string(), 1)
+            ]
+        );
+    }
+
+    #[test]
+    fn resolve_names() {
+        let mut ar = registry(&[]);
+        let foo = asset(&mut ar, "/Game/Foo", "Blueprint");
+        let resolved = foo.resolve(&ar.names);
+        assert_eq!(
+            resolved,
+            ResolvedFields {
+                object_path: "/Game/Foo.Foo",
+                package_path: "/Game",
+                asset_class: "Blueprint",
+                package_name: Some("/Game/Foo"),
+                asset_name: Some("Foo"),
+            }
+        );
+
+        let export_path = ExportPath {
+            object_path: foo.object_path,
+            package_path: foo.package_path,
+            asset_class: foo.asset_class,
+        };
+        let resolved = export_path.resolve(&ar.names);
+        assert_eq!(resolved.object_path, "/Game/Foo.Foo");
+        assert_eq!(resolved.package_path, "/Game");
+        assert_eq!(resolved.asset_class, "Blueprint");
+        assert_eq!((resolved.package_name, resolved.asset_name), (None, None));
+    }
+
+    #[test]
+    fn roundtrip_invariant() {
+        let mut ar = registry(&["Foo"]);
+        let mut buf = vec![];
+        ar.write(&mut buf).unwrap();
+        assert!(super::roundtrip_invariant(&buf));
+
+        let foo = asset(&mut ar, "/Game/Foo", "Blueprint");
+        ar.asset_data.push(foo);
+        ar.add_tags(0, [("Key", "Value"), ("Wide", "Näme")])
+            .unwrap();
+        ar.store.texts.push("text".to_string());
+        buf.clear();
+        ar.write(&mut buf).unwrap();
+        assert!(super::roundtrip_invariant(&buf));
+
+        assert!(!super::roundtrip_invariant(&[]));
+        assert!(!super::roundtrip_invariant(&buf[..buf.len() / 2]));
+        assert!(!super::roundtrip_invariant(&[0xff; 64]));
+
+        // names are compared in order
+        let names = |names: &[&str]| Names::new(names.iter().map(|n| n.to_string()).collect());
+        assert_ne!(names(&["Foo", "Bar"]), names(&["Bar", "Foo"]));
+    }
+
+    #[test]
+    fn registry_fields_for() {
+        assert_eq!(
+            super::registry_fields_for("/Game/Foo/Bar", "Bar_C", "BlueprintGeneratedClass")
+                .unwrap(),
+            RegistryFields {
+                object_path: "/Game/Foo/Bar.Bar_C".to_string(),
+                package_path: "/Game/Foo".to_string(),
+                asset_class: "BlueprintGeneratedClass".to_string(),
+                package_name: "/Game/Foo/Bar".to_string(),
+                asset_name: "Bar_C".to_string(),
+            }
+        );
+
+        let game_path =
+            pak_path_to_game_path("Engine/Plugins/Runtime/MyPlugin/Content/Maps/Level").unwrap();
+        let fields = super::registry_fields_for(&game_path, "Level", "World").unwrap();
+        assert_eq!(fields.object_path, "/MyPlugin/Maps/Level.Level");
+        assert_eq!(fields.package_path, "/MyPlugin/Maps");
+        assert_eq!(fields.package_name, "/MyPlugin/Maps/Level");
+    }
+
+    #[cfg(feature = "rayon")]
+    #[test]
+    fn insert_populated_many() {
+        use rayon::iter::{IntoParallelIterator as _, ParallelIterator as _};
+
+        let mut fields = vec![];
+        for i in 0..50 {
+            let game_path = format!("/Game/Dir{}/Asset{i}", i % 5);
+            fields.push(
+                super::registry_fields_for(
+                    &game_path,
+                    &format!("Asset{i}_C"),
+                    "BlueprintGeneratedClass",
+                )
+                .unwrap(),
+            );
+            // already added alongside the generated class
+            fields.push(
+                super::registry_fields_for(&game_path, &format!("Asset{i}"), "Blueprint").unwrap(),
+            );
+        }
+        fields.push(fields[0].clone());
+
+        let mut serial = registry(&[]);
+        for fields in fields.clone() {
+            serial.insert_populated(fields, 0).unwrap();
+        }
+        let mut parallel = registry(&[]);
+        parallel
+            .insert_populated_many(fields.into_par_iter().map(Ok))
+            .unwrap();
+
+        assert_eq!(parallel, serial);
+        assert_eq!(parallel.asset_data.len(), 100);
+        let unique: BTreeSet<&str> = parallel.names.iter().collect();
+        assert_eq!(unique.len(), parallel.names.len());
+    }
+
+    #[test]
+    fn reserve() {
+        let build = |reserve: bool| {
+            let mut ar = registry(&[]);
+            if reserve {
+                ar.reserve_names(100);
+                ar.reserve_assets(20);
+                assert!(ar.names.capacity() >= 100);
+                assert!(ar.asset_data.capacity() >= 20);
+            }
+            for i in 0..20 {
+                let a = asset(&mut ar, &format!("/Game/Asset{i}"), "Blueprint");
+                ar.asset_data.push(a);
+            }
+            ar
+        };
+        assert_eq!(build(true), build(false));
+    }
+
+    #[test]
+    fn retain() {
+        let mut ar = registry(&[]);
+        for package in [
+            "/Game/Other/A",
+            "/Game/MyMod/B",
+            "/Game/Other/C",
+            "/Game/MyMod/D",
+        ] {
+            let a = asset(&mut ar, package, "Blueprint");
+            ar.asset_data.push(a);
+        }
+        for i in 0..4 {
+            let name = format!("Tag{i}");
+            ar.add_tags(i, [(name.as_str(), "value")]).unwrap();
+        }
+        ar.add_tags(1, [("Extra", "more")]).unwrap();
+
+        ar.retain(|a| a.package_path() == "/Game/MyMod");
+        ar.validate().unwrap();
+
+        let packages: Vec<_> = ar.assets().map(|a| a.package_name()).collect();
+        assert_eq!(packages, ["/Game/MyMod/B", "/Game/MyMod/D"]);
+        assert_eq!(ar.store.pairs.len(), 3);
+
+        let b = ar.resolve(&ar.asset_data[0]);
+        assert_eq!(
+            b.tag_value("Tag1"),
+            Some(TagValue::AnsiString("value".into()))
+        );
+        assert_eq!(
+            b.tag_value("Extra"),
+            Some(TagValue::AnsiString("more".into()))
+        );
+        let d = ar.resolve(&ar.asset_data[1]);
+        assert_eq!(
+            d.tag_value("Tag3"),
+            Some(TagValue::AnsiString("value".into()))
+        );
+
+        let keys: Vec<_> = ar
+            .store
+            .pairs
+            .iter()
+            .map(|p| ar.names[p.name].as_str())
+            .collect();
+        assert!(!keys.contains(&"Tag0") && !keys.contains(&"Tag2"));
+    }
+
+    #[test]
+    fn subset() {
+        let mut ar = registry(&["Unused"]);
+        for package in ["/Game/ModA/B", "/Game/Other/C", "/Game/ModA/Sub/D"] {
+            let a = asset(&mut ar, package, "Blueprint");
+            ar.asset_data.push(a);
+        }
+        ar.add_tags(0, [("Author", "me")]).unwrap();
+        ar.add_tags(1, [("Secret", "other")]).unwrap();
+        ar.add_tags(2, [("Author", "Fels\u{f6}")]).unwrap();
+
+        let subset = ar.subset("/Game/ModA/");
+        subset.validate().unwrap();
+        let packages: Vec<_> = subset.assets().map(|a| a.package_name()).collect();
+        assert_eq!(packages, ["/Game/ModA/B", "/Game/ModA/Sub/D"]);
+        for (i, j) in [(0, 0), (1, 2)] {
+            assert_eq!(
+                subset
+                    .resolve(&subset.asset_data[i])
+                    .tags()
+                    .collect::<Vec<_>>(),
+                ar.resolve(&ar.asset_data[j]).tags().collect::<Vec<_>>()
+            );
+        }
+
+        assert_eq!(subset.store.pairs.len(), 2);
+        assert_eq!(subset.store.ansi_strings, ["me"]);
+        assert!(!subset.names.contains("Unused"));
+        assert!(!subset.names.contains("Secret"));
+        assert!(!subset.names.contains("/Game/Other"));
+        assert_eq!(
+            subset.dependencies,
+            DependencySection::Parsed(Dependencies::default())
+        );
+
+        let bytes = subset.write_to_vec().unwrap();
+        assert_eq!(AssetRegistry::read_from_slice(&bytes).unwrap(), subset);
+        // the source registry is untouched
+        assert_eq!(ar.asset_data.len(), 3);
+
+        // bad maps and pairs are dropped rather than copied
+        ar.add_tags(0, [("Tier", "1")]).unwrap();
+        let author = ar.asset_data[0].tags.pair_begin as usize;
+        ar.store.pairs[author].index = 99;
+        ar.asset_data[2].tags.num = 9;
+        let subset = ar.subset("/Game/ModA/");
+        subset.validate().unwrap();
+        let tags: Vec<_> = subset.resolve(&subset.asset_data[0]).tags().collect();
+        assert_eq!(tags, [("Tier", TagValue::AnsiString("1".to_string()))]);
+        assert_eq!(subset.asset_data[1].tags.num, 0);
+    }
+
+    #[test]
+    fn strip_editor_only() {
+        let mut ar = sample_registry();
+        ar.add_tags(
+            2,
+            [
+                ("AssetImportData", "[{\"RelativeFilename\":\"rock.png\"}]"),
+                ("ImportedSize", "256"),
+            ],
+        )
+        .unwrap();
+        ar.add_tags(0, [("assetimportdata", "[]")]).unwrap();
+        let before = ar.serialized_size().unwrap();
+        let strings = ar.store.ansi_strings.len();
+
+        ar.strip_editor_only();
+        ar.validate().unwrap();
+        assert!(ar.serialized_size().unwrap() < before);
+        assert_eq!(ar.store.ansi_strings.len(), strings - 2);
+        assert_eq!(ar.store.pairs.len(), 4);
+        for asset in ar.assets() {
+            assert_eq!(asset.tag_value("AssetImportData"), None);
+        }
+
+        let rock = ar.resolve(&ar.asset_data[2]);
+        assert_eq!(
+            rock.tags().map(|(k, _)| k).collect::<Vec<_>>(),
+            ["Format", "DisplayName", "ImportedSize"]
+        );
+        assert_eq!(
+            rock.tag_value("ImportedSize"),
+            Some(TagValue::AnsiString("256".into()))
+        );
+        let bar = ar.resolve(&ar.asset_data[0]);
+        assert_eq!(
+            bar.tag_value("ParentClass"),
+            Some(TagValue::SoftObjectPath("/Script/Engine.Actor".into()))
+        );
+    }
+
+    #[test]
+    fn name_usage_count() {
+        let mut ar = registry(&["Unused"]);
+        let foo = asset(&mut ar, "/Game/Foo", "Blueprint");
+        ar.asset_data.push(foo);
+        // once as a tag key and once as a tag value
+        ar.add_tags(0, [("Tint", "red")]).unwrap();
+        let tint = ar.get_name("Tint");
+        ar.store.nbl_names.push(tint);
+
+        assert_eq!(ar.name_usage_count(tint.into()), 2);
+
+        let count = |ar: &mut AssetRegistry, name: &str| {
+            let name = ar.get_name(name);
+            ar.name_usage_count(name.into())
+        };
+        assert_eq!(count(&mut ar, "/Game"), 1);
+        assert_eq!(count(&mut ar, "Foo"), 1);
+        assert_eq!(count(&mut ar, "red"), 0);
+        assert_eq!(count(&mut ar, "Unused"), 0);
+    }
+
+    #[test]
+    fn overlay() {
+        let base = sample_registry();
+        let mut overlay = OverlayRegistry::new(&base);
+        overlay.add_asset("/Game/Foo/Baz.Baz", "Blueprint").unwrap();
+        assert!(overlay.add_asset("/Game/Foo/Bar.Bar", "Blueprint").is_err());
+
+        // only the names missing from the base are stored in the overlay
+        let names: Vec<_> = overlay.extra_names.iter().collect();
+        assert_eq!(names, ["/Game/Foo/Baz.Baz", "/Game/Foo/Baz", "Baz"]);
+        let added = &overlay.added[0];
+        assert_eq!(
+            added.package_path,
+            base.find_by_object_path("/Game/Foo/Bar.Bar")
+                .unwrap()
+                .package_path
+        );
+        assert_ne!(added.object_path.0 & OVERLAY_NAME, 0);
+        assert_eq!(overlay.name(added.asset_name), "Baz");
+        assert_eq!(overlay.name(added.asset_class), "Blueprint");
+
+        let found = overlay.find_by_object_path("/Game/Foo/Baz.Baz").unwrap();
+        assert_eq!(found, &overlay.added[0]);
+        let found = overlay
+            .find_by_object_path("/Game/Textures/T_Rock.T_Rock")
+            .unwrap();
+        assert_eq!(found, &base.asset_data[2]);
+        assert!(overlay
+            .find_by_object_path("/Game/Missing.Missing")
+            .is_none());
+        assert_eq!(overlay.object_paths().count(), 4);
+    }
+
+    #[test]
+    fn sub_object_paths() {
+        let mut ar = sample_registry();
+        let component = crate::paths::sub_object_path("/Game/Foo/Bar.Bar", "Component");
+        ar.apply_patch(RegistryPatch {
+            add: vec![AssetSpec {
+                object_path: component.clone(),
+                asset_class: "SceneComponent".into(),
+            }],
+            ..Default::default()
+        })
+        .unwrap();
+
+        let added = ar.resolve(ar.find_by_object_path(&component).unwrap());
+        assert_eq!(added.object_path(), "/Game/Foo/Bar.Bar:Component");
+        assert_eq!(added.package_name(), "/Game/Foo/Bar");
+        assert_eq!(added.package_path(), "/Game/Foo");
+        assert_eq!(added.asset_name(), "Component");
+        // the outer asset is still found by its own path
+        let outer = ar.resolve(ar.find_by_object_path("/Game/Foo/Bar.Bar").unwrap());
+        assert_eq!(outer.asset_class(), "Blueprint");
+    }
+
+    #[test]
+    fn remap_paths() {
+        let mut ar = registry(&[]);
+        let moved = asset(&mut ar, "/Game/A/Foo", "Blueprint");
+        let mut referencing = asset(&mut ar, "/Game/C/Bar", "Blueprint");
+        referencing.tags = MapHandle {
+            has_numberless_keys: true,
+            num: 1,
+            pair_begin: 0,
+        };
+        ar.asset_data.extend([moved, referencing]);
+
+        let export_path = ExportPath {
+            object_path: ar.get_name("/Game/A/Foo.Foo"),
+            package_path: ar.get_name("/Game/A"),
+            asset_class: ar.get_name("Blueprint"),
+        };
+        ar.store.export_paths.push(export_path);
+        let key = ar.get_name("Target");
+        ar.store.pairs.push(Pair {
+            name: NameIndex(key.0),
+            type_: Type::ExportPath,
+            index: 0,
+        });
+
+        ar.remap_paths(|path| {
+            if path == "/Game/A" {
+                Some("/Game/B".to_string())
+            } else {
+                path.strip_prefix("/Game/A/")
+                    .map(|rest| format!("/Game/B/{rest}"))
+            }
+        });
+
+        let names = &ar.names;
+        assert_eq!(names[ar.asset_data[0].object_path], "/Game/B/Foo.Foo");
+        assert_eq!(names[ar.asset_data[0].package_path], "/Game/B");
+        assert_eq!(names[ar.asset_data[0].package_name], "/Game/B/Foo");
+        assert_eq!(names[ar.asset_data[0].asset_name], "Foo");
+        assert_eq!(names[ar.asset_data[1].object_path], "/Game/C/Bar.Bar");
+        let tag = &ar.store.export_paths[0];
+        assert_eq!(names[tag.object_path], "/Game/B/Foo.Foo");
+        assert_eq!(names[tag.package_path], "/Game/B");
+        assert_eq!(names[tag.asset_class], "Blueprint");
+    }
+
+    #[test]
+    fn remap_soft_object_path() {
+        let mut ar = registry(&[]);
+        let bar = asset(&mut ar, "/Game/C/Bar", "Blueprint");
+        ar.asset_data.push(bar);
+        ar.add_tags(
+            0,
+            [("SoftRef", "/Game/A/Foo.Foo"), ("Plain", "/Game/A/Foo")],
+        )
+        .unwrap();
+        let bar = &ar.asset_data[0];
+        assert_eq!(
+            ar.tag_value(bar, "SoftRef"),
+            Some(TagValue::SoftObjectPath("/Game/A/Foo.Foo".to_string()))
+        );
+
+        ar.remap_paths(|path| {
+            path.strip_prefix("/Game/A/")
+                .map(|rest| format!("/Game/B/{rest}"))
+        });
+
+        let bar = &ar.asset_data[0];
+        assert_eq!(
+            ar.tag_value(bar, "SoftRef"),
+            Some(TagValue::SoftObjectPath("/Game/B/Foo.Foo".to_string()))
+        );
+        assert_eq!(
+            ar.tag_value(bar, "Plain"),
+            Some(TagValue::AnsiString("/Game/A/Foo".to_string()))
+        );
+    }
+
     /*
     use super::*;
 
@@ -798,7 +4474,7 @@ mod test {
         std::fs::write("../input.txt", dump_ar(&ar)).unwrap();
 
         ar.asset_data.clear();
-        ar.names.0.clear();
+        ar.names = Names::default();
 
         for file in pak.files() {
             let path = crate::paths::PakPath::new(&file);
@@ -0,0 +1,104 @@
+use std::fmt::{self, Display, Formatter};
+
+pub type Result<T, E = UAssetError> = std::result::Result<T, E>;
+
+/// Errors returned when reading, writing or editing an asset registry
+#[derive(Debug)]
+pub enum UAssetError {
+    /// A section marker did not match
+    BadMagic {
+        expected: u32,
+        found: u32,
+    },
+    /// Input ended before a value was fully read
+    Truncated,
+    /// Unknown value type in the tag store
+    InvalidType(u32),
+    /// An index or range points outside the data it refers to
+    IndexOutOfRange(String),
+    /// The registry version is not one that can be handled
+    UnsupportedVersion(u32),
+    /// A name or string is not valid UTF-8 or UTF-16
+    Utf8,
+    /// Malformed data or an argument that conflicts with the registry, such as a missing
+    /// builder field or an asset that already exists
+    InvalidInput(String),
+    Io(std::io::Error),
+    Other(anyhow::Error),
+    /// Another error annotated with the input offset reading had reached when it occurred
+    AtOffset {
+        offset: u64,
+        error: Box<UAssetError>,
+    },
+}
+
+impl UAssetError {
+    /// Input offset the error occurred at, if known
+    pub fn offset(&self) -> Option<u64> {
+        match self {
+            Self::AtOffset { offset, .. } => Some(*offset),
+            _ => None,
+        }
+    }
+    /// The error without any offset annotation
+    pub fn inner(&self) -> &UAssetError {
+        match self {
+            Self::AtOffset { error, .. } => error.inner(),
+            _ => self,
+        }
+    }
+}
+
+impl Display for UAssetError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::BadMagic { expected, found } => {
+                write!(f, "bad magic: expected {expected:#x}, found {found:#x}")
+            }
+            Self::Truncated => write!(f, "unexpected end of input"),
+            Self::InvalidType(v) => write!(f, "invalid AssetRegistry type: {v}"),
+            Self::IndexOutOfRange(msg) => write!(f, "index out of range: {msg}"),
+            Self::UnsupportedVersion(v) => write!(f, "unsupported version: {v}"),
+            Self::Utf8 => write!(f, "invalid string encoding"),
+            Self::InvalidInput(msg) => write!(f, "{msg}"),
+            Self::Io(e) => write!(f, "{e}"),
+            Self::Other(e) => write!(f, "{e}"),
+            Self::AtOffset { offset, error } => write!(f, "{error} at offset {offset:#x}"),
+        }
+    }
+}
+
+impl std::error::Error for UAssetError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Self::Io(e) => Some(e),
+            Self::Other(e) => Some(e.as_ref()),
+            _ => None,
+        }
+    }
+}
+
+impl From<std::io::Error> for UAssetError {
+    fn from(e: std::io::Error) -> Self {
+        if e.kind() == std::io::ErrorKind::UnexpectedEof {
+            Self::Truncated
+        } else {
+            Self::Io(e)
+        }
+    }
+}
+impl From<std::string::FromUtf8Error> for UAssetError {
+    fn from(_: std::string::FromUtf8Error) -> Self {
+        Self::Utf8
+    }
+}
+impl From<std::string::FromUtf16Error> for UAssetError {
+    fn from(_: std::string::FromUtf16Error) -> Self {
+        Self::Utf8
+    }
+}
+impl From<anyhow::Error> for UAssetError {
+    fn from(e: anyhow::Error) -> Self {
+        Self::Other(e)
+    }
+}
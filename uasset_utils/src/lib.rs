@@ -1,10 +1,35 @@
 pub mod asset_registry;
+pub mod error;
 pub mod paths;
-use std::io::{Read, Seek};
+use std::io::{BufReader, BufWriter, Read, Seek, Write as _};
+use std::{fs::File, path::Path};
 
+use asset_registry::{AssetRegistry, Readable as _, Writable as _};
 use unreal_asset::{exports::ExportBaseTrait as _, types::PackageIndex, Asset};
 
 pub mod splice;
+#[cfg(test)]
+pub(crate) mod test_util;
+
+/// Commonly used types and functions
+///
+/// Only the crate root [`get_root_export`] is re-exported, not the stricter
+/// [`asset_registry::get_root_export`].
+///
+/// ```
+/// use uasset_utils::prelude::*;
+///
+/// let game_path = pak_path_to_game_path("FSD/Content/Foo/Bar").unwrap();
+/// assert_eq!(game_path, "/Game/Foo/Bar");
+/// assert_eq!(Type::try_from(3).unwrap(), Type::Name);
+/// ```
+pub mod prelude {
+    pub use crate::asset_registry::{AssetData, AssetRegistry, Names, Readable, Type, Writable};
+    pub use crate::error::UAssetError;
+    pub use crate::get_root_export;
+    pub use crate::paths::pak_path_to_game_path;
+    pub use crate::{read_asset_registry, write_asset_registry};
+}
 
 pub fn get_root_export<R: Read + Seek>(asset: &Asset<R>) -> Option<PackageIndex> {
     for (i, e) in asset.asset_data.exports.iter().enumerate() {
@@ -15,3 +40,41 @@ pub fn get_root_export<R: Read + Seek>(asset: &Asset<R>) -> Option<PackageIndex>
     }
     None
 }
+
+/// Reads a registry such as `AssetRegistry.bin` from a file
+pub fn read_asset_registry(path: impl AsRef<Path>) -> error::Result<AssetRegistry> {
+    AssetRegistry::read(&mut BufReader::new(File::open(path)?))
+}
+
+/// Writes a registry to a file, replacing it if it exists
+pub fn write_asset_registry(path: impl AsRef<Path>, registry: &AssetRegistry) -> error::Result<()> {
+    let mut writer = BufWriter::new(File::create(path)?);
+    registry.write(&mut writer)?;
+    writer.flush()?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn registry_file_roundtrip() {
+        let path =
+            std::env::temp_dir().join(format!("uasset_utils_registry_{}.bin", std::process::id()));
+        let registry = test_util::sample_registry();
+        write_asset_registry(&path, &registry).unwrap();
+        assert_eq!(
+            std::fs::read(&path).unwrap(),
+            test_util::SAMPLE_REGISTRY_BIN
+        );
+        let read = read_asset_registry(&path);
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(read.unwrap(), registry);
+
+        assert!(matches!(
+            read_asset_registry(&path),
+            Err(error::UAssetError::Io(_))
+        ));
+    }
+}
@@ -40,3 +40,101 @@ pub fn pak_path_to_game_path<P: AsRef<PakPath>>(pak_path: P) -> Option<String> {
     }
     .map(|p| p.to_string())
 }
+
+/// Builds a top level object path: `/Game/Foo/Bar` + `Bar` -> `/Game/Foo/Bar.Bar`
+pub fn object_path(package: &str, object: &str) -> String {
+    format!("{package}.{object}")
+}
+
+/// Appends a sub-object to an object path. The first sub-object level is separated with `:`
+/// and deeper levels with `.`, matching UE's SUBOBJECT_DELIMITER.
+pub fn sub_object_path(object_path: &str, sub_object: &str) -> String {
+    if object_path.contains(':') {
+        format!("{object_path}.{sub_object}")
+    } else {
+        format!("{object_path}:{sub_object}")
+    }
+}
+
+/// Parts of an object path such as `/Game/Foo/Bar.Bar:Component.Inner`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ObjectPath<'a> {
+    /// `/Game/Foo/Bar`
+    pub package_name: &'a str,
+    /// `Bar`, everything between the package name and the sub-object delimiter, so may itself
+    /// contain `.`
+    pub asset_name: &'a str,
+    /// `Component.Inner`, `None` for top level objects
+    pub sub_object: Option<&'a str>,
+}
+impl<'a> ObjectPath<'a> {
+    /// Name of the innermost object: the last sub-object level or the asset name
+    pub fn object_name(&self) -> &'a str {
+        match self.sub_object {
+            Some(sub_object) => sub_object.rsplit('.').next().unwrap(),
+            None => self.asset_name,
+        }
+    }
+}
+
+/// Splits an object path at the first `.` and the first `:` after it, the inverse of
+/// [`object_path`] and [`sub_object_path`]
+pub fn parse_object_path(path: &str) -> Option<ObjectPath<'_>> {
+    let (package_name, object) = path.split_once('.')?;
+    let (asset_name, sub_object) = match object.split_once(':') {
+        Some((asset_name, sub_object)) => (asset_name, Some(sub_object)),
+        None => (object, None),
+    };
+    if package_name.is_empty() || asset_name.is_empty() || sub_object == Some("") {
+        return None;
+    }
+    Some(ObjectPath {
+        package_name,
+        asset_name,
+        sub_object,
+    })
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn object_paths() {
+        let path = object_path("/Game/Foo/Bar", "Bar");
+        assert_eq!(path, "/Game/Foo/Bar.Bar");
+        let component = sub_object_path(&path, "Component");
+        assert_eq!(component, "/Game/Foo/Bar.Bar:Component");
+        assert_eq!(
+            sub_object_path(&component, "Inner"),
+            "/Game/Foo/Bar.Bar:Component.Inner"
+        );
+    }
+
+    #[test]
+    fn parse_object_paths() {
+        let path = parse_object_path("/Game/Foo/Bar.Bar:Component").unwrap();
+        assert_eq!(
+            path,
+            ObjectPath {
+                package_name: "/Game/Foo/Bar",
+                asset_name: "Bar",
+                sub_object: Some("Component"),
+            }
+        );
+        assert_eq!(path.object_name(), "Component");
+
+        let path = parse_object_path("/Game/Foo/Bar.Bar").unwrap();
+        assert_eq!(path.sub_object, None);
+        assert_eq!(path.object_name(), "Bar");
+
+        let path = parse_object_path("/Game/Foo/Bar.Bar.v2:Component.Inner").unwrap();
+        assert_eq!(path.asset_name, "Bar.v2");
+        assert_eq!(path.sub_object, Some("Component.Inner"));
+        assert_eq!(path.object_name(), "Inner");
+
+        assert_eq!(parse_object_path("/Game/Foo/Bar"), None);
+        assert_eq!(parse_object_path("/Game/Foo/Bar.Bar:"), None);
+        assert_eq!(parse_object_path("/Game/Foo/Bar.:Component"), None);
+    }
+}
@@ -11,21 +11,24 @@ use unreal_asset::{
         FStructProperty,
     },
     kismet::{
-        ExByteConst, ExCallMath, ExContext, ExDefaultVariable, ExDynamicCast, ExFalse,
-        ExFloatConst, ExInstanceVariable, ExIntConst, ExJumpIfNot, ExLet, ExLetBool, ExLetObj,
-        ExLocalVariable, ExLocalVirtualFunction, ExNameConst, ExNoObject, ExNothing, ExObjectConst,
+        EExprToken, ExArrayGetByRef, ExByteConst, ExCallMath, ExContext, ExDefaultVariable,
+        ExDynamicCast, ExEndOfScript, ExFalse, ExFieldPathConst, ExFloatConst, ExInstanceVariable,
+        ExIntConst, ExJump, ExJumpIfNot, ExLet, ExLetBool, ExLetObj, ExLocalVariable,
+        ExLocalVirtualFunction, ExNameConst, ExNoObject, ExNothing, ExObjectConst,
+        ExPopExecutionFlow, ExPopExecutionFlowIfNot, ExPrimitiveCast, ExPushExecutionFlow,
         ExReturn, ExSelf, ExSetArray, ExStringConst, ExStructConst, ExStructMemberContext,
         ExTextConst, ExTrue, FieldPath, KismetExpression, KismetExpressionDataTrait,
         KismetPropertyPointer,
     },
     object_version::{ObjectVersion, ObjectVersionUE5},
     reader::archive_trait::ArchiveTrait,
-    types::PackageIndex,
+    types::{fname::FName, PackageIndex},
     Asset, AssetBuilder, Import,
 };
 
 use std::{
-    collections::HashMap,
+    cell::RefCell,
+    collections::{BTreeMap, HashMap},
     fs,
     ops::{Deref, DerefMut},
     path::Path,
@@ -57,6 +60,8 @@ pub struct TrackedStatement {
     pub origin: (Option<String>, PackageIndex),
     pub points_to: Option<(Option<String>, PackageIndex)>,
     pub original_offset: Option<usize>,
+    /// original offsets of removed statements that now resolve to this one
+    pub merged_offsets: Vec<usize>,
     pub ex: KismetExpression,
 }
 
@@ -180,7 +185,10 @@ pub fn walk(ex: &mut KismetExpression, f: &dyn Fn(&mut KismetExpression)) {
         KismetExpression::ExInterfaceContext(ex) => walk(&mut ex.interface_value, &f),
         KismetExpression::ExInterfaceToObjCast(ex) => walk(&mut ex.target, &f),
         KismetExpression::ExJumpIfNot(ex) => walk(&mut ex.boolean_expression, &f),
-        KismetExpression::ExLet(ex) => walk(&mut ex.expression, &f),
+        KismetExpression::ExLet(ex) => {
+            walk(&mut ex.variable, &f);
+            walk(&mut ex.expression, &f);
+        }
         KismetExpression::ExLetBool(ex) => {
             walk(&mut ex.variable_expression, &f);
             walk(&mut ex.assignment_expression, &f);
@@ -269,6 +277,487 @@ pub fn walk(ex: &mut KismetExpression, f: &dyn Fn(&mut KismetExpression)) {
     }
 }
 
+/// walk all expressions and subexpressions without modifying them, visiting subexpressions
+/// in serialization order
+pub fn walk_ref<'a>(ex: &'a KismetExpression, f: &dyn Fn(&'a KismetExpression)) {
+    f(ex);
+    for (_, ex) in layout(ex) {
+        walk_ref(ex, f);
+    }
+}
+
+/// in-memory size of an object or property pointer operand, which bytecode offsets count
+/// even though packages store them as 4 byte indexes
+const POINTER_SIZE: usize = 8;
+/// in-memory size of a script name operand
+const NAME_SIZE: usize = 12;
+
+/// subexpressions of `ex` in serialization order, each with the number of operand bytes
+/// written between the previous subexpression (or the token) and it
+fn layout(ex: &KismetExpression) -> Vec<(usize, &KismetExpression)> {
+    // fields are boxed or not depending on the expression
+    fn sub<E>(gap: usize, ex: &E) -> (usize, &KismetExpression)
+    where
+        E: std::borrow::Borrow<KismetExpression>,
+    {
+        (gap, std::borrow::Borrow::borrow(ex))
+    }
+    fn params(gap: usize, params: &[KismetExpression]) -> Vec<(usize, &KismetExpression)> {
+        params
+            .iter()
+            .enumerate()
+            .map(|(i, ex)| (if i == 0 { gap } else { 0 }, ex))
+            .collect()
+    }
+    match ex {
+        KismetExpression::ExFieldPathConst(ex) => vec![sub(0, &ex.value)],
+        KismetExpression::ExSoftObjectConst(ex) => vec![sub(0, &ex.value)],
+        KismetExpression::ExAddMulticastDelegate(ex) => {
+            vec![sub(0, &ex.delegate), sub(0, &ex.delegate_to_add)]
+        }
+        KismetExpression::ExRemoveMulticastDelegate(ex) => {
+            vec![sub(0, &ex.delegate), sub(0, &ex.delegate_to_add)]
+        }
+        KismetExpression::ExArrayConst(ex) => params(POINTER_SIZE + 4, &ex.elements),
+        KismetExpression::ExSetConst(ex) => params(POINTER_SIZE + 4, &ex.elements),
+        KismetExpression::ExMapConst(ex) => params(2 * POINTER_SIZE + 4, &ex.elements),
+        KismetExpression::ExStructConst(ex) => params(POINTER_SIZE + 4, &ex.value),
+        KismetExpression::ExArrayGetByRef(ex) => {
+            vec![sub(0, &ex.array_variable), sub(0, &ex.array_index)]
+        }
+        KismetExpression::ExAssert(ex) => vec![sub(2 + 1, &ex.assert_expression)],
+        KismetExpression::ExBindDelegate(ex) => {
+            vec![sub(NAME_SIZE, &ex.delegate), sub(0, &ex.object_term)]
+        }
+        KismetExpression::ExCallMath(ex) => params(POINTER_SIZE, &ex.parameters),
+        KismetExpression::ExFinalFunction(ex) => params(POINTER_SIZE, &ex.parameters),
+        KismetExpression::ExLocalFinalFunction(ex) => params(POINTER_SIZE, &ex.parameters),
+        KismetExpression::ExVirtualFunction(ex) => params(NAME_SIZE, &ex.parameters),
+        KismetExpression::ExLocalVirtualFunction(ex) => params(NAME_SIZE, &ex.parameters),
+        KismetExpression::ExCallMulticastDelegate(ex) => {
+            let mut layout = vec![sub(POINTER_SIZE, &ex.delegate)];
+            layout.extend(params(0, &ex.parameters));
+            layout
+        }
+        KismetExpression::ExClassContext(ex) => vec![
+            sub(0, &ex.object_expression),
+            sub(4 + POINTER_SIZE, &ex.context_expression),
+        ],
+        KismetExpression::ExContext(ex) => vec![
+            sub(0, &ex.object_expression),
+            sub(4 + POINTER_SIZE, &ex.context_expression),
+        ],
+        KismetExpression::ExContextFailSilent(ex) => vec![
+            sub(0, &ex.object_expression),
+            sub(4 + POINTER_SIZE, &ex.context_expression),
+        ],
+        KismetExpression::ExClearMulticastDelegate(ex) => vec![sub(0, &ex.delegate_to_clear)],
+        KismetExpression::ExComputedJump(ex) => vec![sub(0, &ex.code_offset_expression)],
+        KismetExpression::ExCrossInterfaceCast(ex) => vec![sub(POINTER_SIZE, &ex.target)],
+        KismetExpression::ExInterfaceToObjCast(ex) => vec![sub(POINTER_SIZE, &ex.target)],
+        KismetExpression::ExObjToInterfaceCast(ex) => vec![sub(POINTER_SIZE, &ex.target)],
+        KismetExpression::ExDynamicCast(ex) => vec![sub(POINTER_SIZE, &ex.target_expression)],
+        KismetExpression::ExMetaCast(ex) => vec![sub(POINTER_SIZE, &ex.target_expression)],
+        KismetExpression::ExPrimitiveCast(ex) => vec![sub(1, &ex.target)],
+        KismetExpression::ExInterfaceContext(ex) => vec![sub(0, &ex.interface_value)],
+        KismetExpression::ExJumpIfNot(ex) => vec![sub(4, &ex.boolean_expression)],
+        KismetExpression::ExPopExecutionFlowIfNot(ex) => vec![sub(0, &ex.boolean_expression)],
+        KismetExpression::ExSkip(ex) => vec![sub(4, &ex.skip_expression)],
+        KismetExpression::ExLet(ex) => {
+            vec![sub(POINTER_SIZE, &ex.variable), sub(0, &ex.expression)]
+        }
+        KismetExpression::ExLetBool(ex) => {
+            vec![
+                sub(0, &ex.variable_expression),
+                sub(0, &ex.assignment_expression),
+            ]
+        }
+        KismetExpression::ExLetDelegate(ex) => {
+            vec![
+                sub(0, &ex.variable_expression),
+                sub(0, &ex.assignment_expression),
+            ]
+        }
+        KismetExpression::ExLetMulticastDelegate(ex) => {
+            vec![
+                sub(0, &ex.variable_expression),
+                sub(0, &ex.assignment_expression),
+            ]
+        }
+        KismetExpression::ExLetObj(ex) => {
+            vec![
+                sub(0, &ex.variable_expression),
+                sub(0, &ex.assignment_expression),
+            ]
+        }
+        KismetExpression::ExLetWeakObjPtr(ex) => {
+            vec![
+                sub(0, &ex.variable_expression),
+                sub(0, &ex.assignment_expression),
+            ]
+        }
+        KismetExpression::ExLetValueOnPersistentFrame(ex) => {
+            vec![sub(POINTER_SIZE, &ex.assignment_expression)]
+        }
+        KismetExpression::ExReturn(ex) => vec![sub(0, &ex.return_expression)],
+        KismetExpression::ExSetArray(ex) => match &ex.assigning_property {
+            Some(property) => {
+                let mut layout = vec![sub(0, property)];
+                layout.extend(params(0, &ex.elements));
+                layout
+            }
+            None => params(POINTER_SIZE, &ex.elements),
+        },
+        KismetExpression::ExSetMap(ex) => {
+            let mut layout = vec![sub(0, &ex.map_property)];
+            layout.extend(params(4, &ex.elements));
+            layout
+        }
+        KismetExpression::ExSetSet(ex) => {
+            let mut layout = vec![sub(0, &ex.set_property)];
+            layout.extend(params(4, &ex.elements));
+            layout
+        }
+        KismetExpression::ExStructMemberContext(ex) => {
+            vec![sub(POINTER_SIZE, &ex.struct_expression)]
+        }
+        KismetExpression::ExSwitchValue(ex) => {
+            let mut layout = vec![sub(2 + 4, &ex.index_term)];
+            for case in &ex.cases {
+                layout.push(sub(0, &case.case_index_value_term));
+                layout.push(sub(4, &case.case_term));
+            }
+            layout.push(sub(0, &ex.default_term));
+            layout
+        }
+        _ => vec![],
+    }
+}
+
+/// call `f` with the absolute offset of every statement and the subexpressions [`walk`]
+/// visits, parents before their subexpressions
+/// offsets count pointer operands at their in-memory size like jump targets do
+pub fn walk_with_offsets(
+    version: AssetVersion,
+    exprs: &[KismetExpression],
+    mut f: impl FnMut(u32, &KismetExpression),
+) -> Result<()> {
+    fn visit(
+        version: AssetVersion,
+        offset: usize,
+        ex: &KismetExpression,
+        f: &mut dyn FnMut(u32, &KismetExpression),
+    ) -> Result<()> {
+        f(offset as u32, ex);
+        // past the token
+        let mut offset = offset + 1;
+        for (gap, child) in layout(ex) {
+            offset += gap;
+            visit(version, offset, child, f)?;
+            offset += get_size(child, version)?;
+        }
+        Ok(())
+    }
+    let mut offset = 0;
+    for ex in exprs {
+        visit(version, offset, ex, &mut f)?;
+        offset += get_size(ex, version)?;
+    }
+    Ok(())
+}
+
+/// findings from scanning parsed bytecode with [`parse_report`]
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct ParseReport {
+    /// offset and token of each deprecated opcode
+    pub deprecated_ops: Vec<(u32, EExprToken)>,
+}
+
+/// scan parsed bytecode for deprecated opcodes, which point to stale cooked content or a
+/// parse that went out of sync
+/// callers decide whether a non-empty report is an error
+pub fn parse_report(version: AssetVersion, exprs: &[KismetExpression]) -> Result<ParseReport> {
+    let mut report = ParseReport::default();
+    walk_with_offsets(version, exprs, |offset, ex| {
+        if let KismetExpression::ExDeprecatedOp4A(ex) = ex {
+            report.deprecated_ops.push((offset, ex.token));
+        }
+    })?;
+    Ok(report)
+}
+
+/// walk all expressions and subexpressions, calling `f` on every property pointer they hold
+pub fn walk_property_pointers(ex: &mut KismetExpression, f: &dyn Fn(&mut KismetPropertyPointer)) {
+    walk(ex, &|ex| match ex {
+        KismetExpression::ExLocalVariable(ex) => f(&mut ex.variable),
+        KismetExpression::ExInstanceVariable(ex) => f(&mut ex.variable),
+        KismetExpression::ExDefaultVariable(ex) => f(&mut ex.variable),
+        KismetExpression::ExLocalOutVariable(ex) => f(&mut ex.variable),
+        KismetExpression::ExClassSparseDataVariable(ex) => f(&mut ex.variable),
+        KismetExpression::ExPropertyConst(ex) => f(&mut ex.property),
+        KismetExpression::ExLet(ex) => f(&mut ex.value),
+        KismetExpression::ExLetValueOnPersistentFrame(ex) => f(&mut ex.destination_property),
+        KismetExpression::ExContext(ex) => f(&mut ex.r_value_pointer),
+        KismetExpression::ExContextFailSilent(ex) => f(&mut ex.r_value_pointer),
+        KismetExpression::ExClassContext(ex) => f(&mut ex.r_value_pointer),
+        KismetExpression::ExStructMemberContext(ex) => f(&mut ex.struct_member_expression),
+        KismetExpression::ExArrayConst(ex) => f(&mut ex.inner_property),
+        KismetExpression::ExSetConst(ex) => f(&mut ex.inner_property),
+        KismetExpression::ExMapConst(ex) => {
+            f(&mut ex.key_property);
+            f(&mut ex.value_property);
+        }
+        _ => {}
+    });
+}
+
+/// walk all expressions and subexpressions, calling `f` on every package index they hold
+/// including the owners of property pointers
+pub fn walk_package_indexes(ex: &mut KismetExpression, f: &dyn Fn(&mut PackageIndex)) {
+    walk(ex, &|ex| match ex {
+        KismetExpression::ExObjectConst(ex) => f(&mut ex.value),
+        KismetExpression::ExCallMath(ex) => f(&mut ex.stack_node),
+        KismetExpression::ExFinalFunction(ex) => f(&mut ex.stack_node),
+        KismetExpression::ExLocalFinalFunction(ex) => f(&mut ex.stack_node),
+        KismetExpression::ExCallMulticastDelegate(ex) => f(&mut ex.stack_node),
+        KismetExpression::ExDynamicCast(ex) => f(&mut ex.class_ptr),
+        KismetExpression::ExMetaCast(ex) => f(&mut ex.class_ptr),
+        KismetExpression::ExCrossInterfaceCast(ex) => f(&mut ex.class_ptr),
+        KismetExpression::ExObjToInterfaceCast(ex) => f(&mut ex.class_ptr),
+        KismetExpression::ExInterfaceToObjCast(ex) => f(&mut ex.class_ptr),
+        KismetExpression::ExStructConst(ex) => f(&mut ex.struct_value),
+        KismetExpression::ExSetArray(ex) => {
+            if let Some(pi) = ex.array_inner_prop.as_mut() {
+                f(pi);
+            }
+        }
+        KismetExpression::ExTextConst(ex) => {
+            if let Some(pi) = ex.value.string_table_asset.as_mut() {
+                f(pi);
+            }
+        }
+        _ => {}
+    });
+    walk_property_pointers(ex, &|p| {
+        if let Some(pi) = p.old.as_mut() {
+            f(pi);
+        }
+        if let Some(fp) = p.new.as_mut() {
+            f(&mut fp.resolved_owner);
+        }
+    });
+}
+
+/// replace every package index found in `map` across the bytecode of every function in the
+/// asset
+/// indexes are fixed size operands so jump targets and other offsets stay valid
+pub fn remap_script_imports<C: std::io::Read + std::io::Seek>(
+    asset: &mut Asset<C>,
+    map: &HashMap<PackageIndex, PackageIndex>,
+) {
+    for export in &mut asset.asset_data.exports {
+        let Export::FunctionExport(f) = export else {
+            continue;
+        };
+        for ex in f.struct_export.script_bytecode.iter_mut().flatten() {
+            walk_package_indexes(ex, &|pi| {
+                if let Some(new) = map.get(pi) {
+                    *pi = *new;
+                }
+            });
+        }
+    }
+}
+
+/// collect values of all string and unicode string constants, including the literal and
+/// source strings of text constants
+pub fn collect_string_constants(ex: &KismetExpression) -> Vec<&str> {
+    fn string(ex: &KismetExpression) -> Option<&str> {
+        match ex {
+            KismetExpression::ExStringConst(ex) => Some(&ex.value),
+            KismetExpression::ExUnicodeStringConst(ex) => Some(&ex.value),
+            _ => None,
+        }
+    }
+    let strings = RefCell::new(vec![]);
+    walk_ref(ex, &|ex| match ex {
+        KismetExpression::ExTextConst(ex) => {
+            let text = &ex.value;
+            let fields = [
+                &text.localized_source,
+                &text.literal_string,
+                &text.invariant_literal_string,
+            ];
+            strings
+                .borrow_mut()
+                .extend(fields.into_iter().flatten().filter_map(string));
+        }
+        ex => strings.borrow_mut().extend(string(ex)),
+    });
+    strings.into_inner()
+}
+
+/// count of each opcode across the bytecode of every function in the asset, including
+/// nested expressions
+/// keyed by the opcode byte rather than `EExprToken`, which is not `Ord`, so iteration order
+/// is stable
+pub fn opcode_histogram<C: std::io::Read + std::io::Seek>(asset: &Asset<C>) -> BTreeMap<u8, usize> {
+    let histogram = RefCell::new(BTreeMap::new());
+    for export in &asset.asset_data.exports {
+        let Export::FunctionExport(f) = export else {
+            continue;
+        };
+        for ex in f.struct_export.script_bytecode.iter().flatten() {
+            walk_ref(ex, &|ex| {
+                *histogram
+                    .borrow_mut()
+                    .entry(ex.get_token() as u8)
+                    .or_default() += 1;
+            });
+        }
+    }
+    histogram.into_inner()
+}
+
+/// resolves names and object references in expressions against the asset they belong to
+pub struct ExprCtx<'a, C: std::io::Read + std::io::Seek> {
+    pub asset: &'a Asset<C>,
+}
+impl<C: std::io::Read + std::io::Seek> ExprCtx<'_, C> {
+    pub fn resolve_name(&self, name: &FName) -> String {
+        name.get_owned_content()
+    }
+    /// object name of the referenced import or export, "null" for a null index
+    pub fn resolve_index(&self, index: &PackageIndex) -> String {
+        if index.index == 0 {
+            "null".to_string()
+        } else if index.is_import() {
+            self.asset
+                .get_import(*index)
+                .map(|i| i.object_name.get_owned_content())
+                .unwrap_or_else(|| format!("<invalid import {}>", index.index))
+        } else {
+            self.asset
+                .get_export(*index)
+                .map(|e| e.get_base_export().object_name.get_owned_content())
+                .unwrap_or_else(|| format!("<invalid export {}>", index.index))
+        }
+    }
+}
+
+/// `self`
+pub fn self_expression() -> KismetExpression {
+    ExSelf {
+        token: EExprToken::ExSelf,
+    }
+    .into()
+}
+/// `value` as an `int32` literal
+pub fn int_expression(value: i32) -> KismetExpression {
+    ExIntConst {
+        token: EExprToken::ExIntConst,
+        value,
+    }
+    .into()
+}
+/// `value` as a `float` literal
+pub fn float_expression(value: f32) -> KismetExpression {
+    ExFloatConst {
+        token: EExprToken::ExFloatConst,
+        value: value.into(),
+    }
+    .into()
+}
+/// `value` as an ANSI string literal
+pub fn string_expression(value: &str) -> KismetExpression {
+    ExStringConst {
+        token: EExprToken::ExStringConst,
+        value: value.to_string(),
+    }
+    .into()
+}
+/// `value` as a name literal
+pub fn name_expression(value: FName) -> KismetExpression {
+    ExNameConst {
+        token: EExprToken::ExNameConst,
+        value,
+    }
+    .into()
+}
+/// reference to the object at `value`
+pub fn object_expression(value: PackageIndex) -> KismetExpression {
+    ExObjectConst {
+        token: EExprToken::ExObjectConst,
+        value,
+    }
+    .into()
+}
+/// `true` or `false`
+pub fn bool_expression(value: bool) -> KismetExpression {
+    if value {
+        ExTrue {
+            token: EExprToken::ExTrue,
+        }
+        .into()
+    } else {
+        ExFalse {
+            token: EExprToken::ExFalse,
+        }
+        .into()
+    }
+}
+/// no-op
+pub fn nothing_expression() -> KismetExpression {
+    ExNothing {
+        token: EExprToken::ExNothing,
+    }
+    .into()
+}
+/// evaluate `context` on `object`, e.g. `object.Function()`
+/// the skip offset is the serialized size of `context` so it stays valid wherever the
+/// expression ends up
+pub fn context_expression(
+    version: AssetVersion,
+    object: KismetExpression,
+    r_value_pointer: KismetPropertyPointer,
+    context: KismetExpression,
+) -> Result<KismetExpression> {
+    Ok(ExContext {
+        token: EExprToken::ExContext,
+        offset: get_size(&context, version)? as u32,
+        object_expression: Box::new(object),
+        r_value_pointer,
+        context_expression: Box::new(context),
+    }
+    .into())
+}
+
+/// recompute the skip sizes of ExSkip and context expressions in `ex` from the current size of
+/// the expressions they skip, for after those expressions were edited
+/// skip sizes are fixed size operands so the order they are updated in does not matter
+pub fn recompute_skip_offsets(version: AssetVersion, ex: &mut KismetExpression) -> Result<()> {
+    let error = RefCell::new(None);
+    walk(ex, &|ex| {
+        let (offset, skipped) = match ex {
+            KismetExpression::ExSkip(ex) => (&mut ex.code_offset, &*ex.skip_expression),
+            KismetExpression::ExContext(ex) => (&mut ex.offset, &*ex.context_expression),
+            KismetExpression::ExContextFailSilent(ex) => (&mut ex.offset, &*ex.context_expression),
+            KismetExpression::ExClassContext(ex) => (&mut ex.offset, &*ex.context_expression),
+            _ => return,
+        };
+        match get_size(skipped, version) {
+            Ok(size) => *offset = size as u32,
+            Err(e) => {
+                error.borrow_mut().get_or_insert(e);
+            }
+        }
+    });
+    match error.into_inner() {
+        Some(e) => Err(e),
+        None => Ok(()),
+    }
+}
+
 /// find and shift any ExSwitchValue
 /// primarily used to make offsets relative before transformation and return them back to absolute
 fn shift_switch(ex: &mut KismetExpression, shift: i32) {
@@ -282,6 +771,318 @@ fn shift_switch(ex: &mut KismetExpression, shift: i32) {
     });
 }
 
+/// clone a function's statements for placement `base_offset` bytes into a script
+/// jump targets, pushed addresses and switch offsets are shifted by `base_offset` rather than
+/// recomputed
+pub fn rebase_function(exprs: &[KismetExpression], base_offset: u32) -> Vec<KismetExpression> {
+    exprs
+        .iter()
+        .map(|ex| {
+            let mut ex = ex.clone();
+            match &mut ex {
+                KismetExpression::ExJump(ex) => ex.code_offset += base_offset,
+                KismetExpression::ExJumpIfNot(ex) => ex.code_offset += base_offset,
+                KismetExpression::ExPushExecutionFlow(ex) => ex.pushing_address += base_offset,
+                _ => {}
+            }
+            shift_switch(&mut ex, base_offset as i32);
+            ex
+        })
+        .collect()
+}
+
+/// indexes of the statements matching `pred`
+pub fn find_pattern(
+    exprs: &[KismetExpression],
+    pred: impl Fn(&KismetExpression) -> bool,
+) -> Vec<usize> {
+    exprs
+        .iter()
+        .enumerate()
+        .filter(|(_, ex)| pred(ex))
+        .map(|(i, _)| i)
+        .collect()
+}
+
+/// insert a copy of `new` after every statement matching `pred`, returning the match count
+/// jumps, pushed addresses and switch offsets are updated to follow the statements they
+/// pointed at, so jumping past a match also skips the inserted statements
+/// offsets stored in latent action info structs are not updated
+pub fn insert_after_matching(
+    version: AssetVersion,
+    exprs: &mut Vec<KismetExpression>,
+    pred: impl Fn(&KismetExpression) -> bool,
+    new: Vec<KismetExpression>,
+) -> Result<usize> {
+    let matches = find_pattern(exprs, &pred);
+    if matches.is_empty() {
+        return Ok(0);
+    }
+
+    let mut result = vec![];
+    // index of each result statement in `exprs`, `None` for inserted ones
+    let mut origin = vec![];
+    for (i, ex) in exprs.iter().enumerate() {
+        result.push(ex.clone());
+        origin.push(Some(i));
+        if matches.binary_search(&i).is_ok() {
+            result.extend(new.iter().cloned());
+            origin.extend(new.iter().map(|_| None));
+        }
+    }
+
+    let offsets = |exprs: &[KismetExpression]| -> Result<Vec<usize>> {
+        let mut offsets = vec![0];
+        for ex in exprs {
+            offsets.push(offsets.last().unwrap() + get_size(ex, version)?);
+        }
+        Ok(offsets)
+    };
+    let old_offsets = offsets(exprs)?;
+    let new_offsets = offsets(&result)?;
+    let mut mapping = HashMap::new();
+    for (j, i) in origin.iter().enumerate() {
+        if let Some(i) = i {
+            mapping.insert(old_offsets[*i], new_offsets[j]);
+        }
+    }
+    mapping.insert(old_offsets[exprs.len()], new_offsets[result.len()]);
+    let remap = |offset: &mut u32| -> Result<()> {
+        *offset = *mapping
+            .get(&(*offset as usize))
+            .ok_or_else(|| anyhow::anyhow!("offset {offset} is not at a statement"))?
+            as u32;
+        Ok(())
+    };
+
+    for (j, ex) in result.iter_mut().enumerate() {
+        let Some(i) = origin[j] else {
+            continue;
+        };
+        match ex {
+            KismetExpression::ExJump(ex) => remap(&mut ex.code_offset)?,
+            KismetExpression::ExJumpIfNot(ex) => remap(&mut ex.code_offset)?,
+            KismetExpression::ExPushExecutionFlow(ex) => remap(&mut ex.pushing_address)?,
+            _ => {}
+        }
+        shift_switch(ex, new_offsets[j] as i32 - old_offsets[i] as i32);
+    }
+    *exprs = result;
+    Ok(matches.len())
+}
+
+/// statement parsed by [`assemble`] with jump targets still named by label
+enum AsmStatement {
+    Expression(KismetExpression),
+    Jump(String),
+    JumpIfNot(String, KismetExpression),
+    PushExecutionFlow(String),
+}
+impl AsmStatement {
+    fn build(&self, target: impl Fn(&str) -> Result<u32>) -> Result<KismetExpression> {
+        Ok(match self {
+            Self::Expression(ex) => ex.clone(),
+            Self::Jump(label) => ExJump {
+                token: EExprToken::ExJump,
+                code_offset: target(label)?,
+            }
+            .into(),
+            Self::JumpIfNot(label, condition) => ExJumpIfNot {
+                token: EExprToken::ExJumpIfNot,
+                code_offset: target(label)?,
+                boolean_expression: Box::new(condition.clone()),
+            }
+            .into(),
+            Self::PushExecutionFlow(label) => ExPushExecutionFlow {
+                token: EExprToken::ExPushExecutionFlow,
+                pushing_address: target(label)?,
+            }
+            .into(),
+        })
+    }
+}
+
+/// cursor over one line of [`assemble`] source
+struct AsmParser<'a> {
+    rest: &'a str,
+}
+impl<'a> AsmParser<'a> {
+    fn skip_whitespace(&mut self) {
+        self.rest = self.rest.trim_start();
+    }
+    fn eat(&mut self, c: char) -> bool {
+        self.skip_whitespace();
+        if let Some(rest) = self.rest.strip_prefix(c) {
+            self.rest = rest;
+            true
+        } else {
+            false
+        }
+    }
+    fn word(&mut self) -> Result<&'a str> {
+        self.skip_whitespace();
+        let end = self
+            .rest
+            .find(|c: char| !(c.is_alphanumeric() || matches!(c, '_' | '-' | '.')))
+            .unwrap_or(self.rest.len());
+        if end == 0 {
+            anyhow::bail!("expected a word at {:?}", self.rest);
+        }
+        let (word, rest) = self.rest.split_at(end);
+        self.rest = rest;
+        Ok(word)
+    }
+    fn number<T: std::str::FromStr>(&mut self) -> Result<T> {
+        let word = self.word()?;
+        word.parse()
+            .map_err(|_| anyhow::anyhow!("invalid number {word:?}"))
+    }
+    fn string(&mut self) -> Result<String> {
+        if !self.eat('"') {
+            anyhow::bail!("expected a string at {:?}", self.rest);
+        }
+        let mut value = String::new();
+        let mut chars = self.rest.char_indices();
+        while let Some((i, c)) = chars.next() {
+            match c {
+                '"' => {
+                    self.rest = &self.rest[i + 1..];
+                    return Ok(value);
+                }
+                '\\' => value.extend(chars.next().map(|(_, c)| c)),
+                c => value.push(c),
+            }
+        }
+        anyhow::bail!("unterminated string")
+    }
+    fn expression(&mut self) -> Result<KismetExpression> {
+        Ok(match self.word()? {
+            "Int" => int_expression(self.number()?),
+            "Float" => float_expression(self.number()?),
+            "String" => string_expression(&self.string()?),
+            "Object" => object_expression(PackageIndex::new(self.number()?)),
+            "True" => bool_expression(true),
+            "False" => bool_expression(false),
+            "Nothing" => nothing_expression(),
+            "Self" => self_expression(),
+            "CallMath" => {
+                let stack_node = PackageIndex::new(self.number()?);
+                if !self.eat('(') {
+                    anyhow::bail!("expected ( after CallMath");
+                }
+                let mut parameters = vec![];
+                if !self.eat(')') {
+                    loop {
+                        parameters.push(self.expression()?);
+                        if self.eat(')') {
+                            break;
+                        }
+                        if !self.eat(',') {
+                            anyhow::bail!("expected , or ) in CallMath parameters");
+                        }
+                    }
+                }
+                ExCallMath {
+                    token: EExprToken::ExCallMath,
+                    stack_node,
+                    parameters,
+                }
+                .into()
+            }
+            other => anyhow::bail!("unknown expression {other:?}"),
+        })
+    }
+    fn statement(&mut self) -> Result<AsmStatement> {
+        let start = self.rest;
+        Ok(match self.word()? {
+            "Jump" => AsmStatement::Jump(self.word()?.to_string()),
+            "JumpIfNot" => {
+                let label = self.word()?.to_string();
+                AsmStatement::JumpIfNot(label, self.expression()?)
+            }
+            "PushExecutionFlow" => AsmStatement::PushExecutionFlow(self.word()?.to_string()),
+            "PopExecutionFlow" => AsmStatement::Expression(
+                ExPopExecutionFlow {
+                    token: EExprToken::ExPopExecutionFlow,
+                }
+                .into(),
+            ),
+            "PopExecutionFlowIfNot" => AsmStatement::Expression(
+                ExPopExecutionFlowIfNot {
+                    token: EExprToken::ExPopExecutionFlowIfNot,
+                    boolean_expression: Box::new(self.expression()?),
+                }
+                .into(),
+            ),
+            "Return" => AsmStatement::Expression(
+                ExReturn {
+                    token: EExprToken::ExReturn,
+                    return_expression: Box::new(self.expression()?),
+                }
+                .into(),
+            ),
+            "EndOfScript" => AsmStatement::Expression(
+                ExEndOfScript {
+                    token: EExprToken::ExEndOfScript,
+                }
+                .into(),
+            ),
+            // not a statement keyword, parse the whole line as an expression
+            _ => {
+                self.rest = start;
+                AsmStatement::Expression(self.expression()?)
+            }
+        })
+    }
+}
+
+/// assemble statements from text, one per line
+/// `name:` defines a label for the offset of the next statement, lines starting with `#` are
+/// comments
+/// statements: `Jump label`, `JumpIfNot label <expr>`, `PushExecutionFlow label`,
+/// `PopExecutionFlow`, `PopExecutionFlowIfNot <expr>`, `Return <expr>`, `EndOfScript` or an
+/// expression
+/// expressions: `Int 1`, `Float 1.5`, `String "text"`, `Object -1`, `True`, `False`,
+/// `Nothing`, `Self` and `CallMath -1 (<expr>, ...)`
+pub fn assemble(version: AssetVersion, src: &str) -> Result<Vec<KismetExpression>> {
+    let mut statements = vec![];
+    let mut labels = HashMap::new();
+    let mut offset = 0;
+    for (n, line) in src.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        if let Some(label) = line.strip_suffix(':') {
+            if labels.insert(label.trim().to_string(), offset).is_some() {
+                anyhow::bail!("line {}: duplicate label {label:?}", n + 1);
+            }
+            continue;
+        }
+        let mut parser = AsmParser { rest: line };
+        let statement = parser
+            .statement()
+            .map_err(|e| anyhow::anyhow!("line {}: {e}", n + 1))?;
+        if !parser.rest.trim().is_empty() {
+            anyhow::bail!("line {}: unexpected {:?}", n + 1, parser.rest);
+        }
+        // jump operands are fixed size so a placeholder target gives the final size
+        offset += get_size(&statement.build(|_| Ok(0))?, version)? as u32;
+        statements.push(statement);
+    }
+    statements
+        .iter()
+        .map(|statement| {
+            statement.build(|label| {
+                labels
+                    .get(label)
+                    .copied()
+                    .ok_or_else(|| anyhow::anyhow!("undefined label {label:?}"))
+            })
+        })
+        .collect()
+}
+
 fn find_ubergraph<C: std::io::Read + std::io::Seek>(asset: &Asset<C>) -> Option<PackageIndex> {
     for (i, e) in asset.asset_data.exports.iter().enumerate() {
         if let unreal_asset::exports::Export::FunctionExport(f) = &e {
@@ -399,7 +1200,10 @@ pub fn copy_expression<C: std::io::Read + std::io::Seek>(from: &Asset<C>, to: &m
         KismetExpression::ExUnicodeStringConst(ex) => ex.clone().into(),
         KismetExpression::ExInt64Const(ex) => ex.clone().into(),
         KismetExpression::ExUInt64Const(ex) => ex.clone().into(),
-        //KismetExpression::ExPrimitiveCast(ex) => {}
+        KismetExpression::ExPrimitiveCast(ex) => ExPrimitiveCast { token: ex.token,
+            conversion_type: ex.conversion_type,
+            target: Box::new(copy_expression(from, to, fn_from, fn_to, &ex.target)),
+        }.into(),
         //KismetExpression::ExSetSet(ex) => {}
         //KismetExpression::ExEndSet(ex) => {}
         //KismetExpression::ExSetMap(ex) => {}
@@ -455,9 +1259,14 @@ pub fn copy_expression<C: std::io::Read + std::io::Seek>(from: &Asset<C>, to: &m
         }.into(),
         //KismetExpression::ExSwitchValue(ex) => {}
         //KismetExpression::ExInstrumentationEvent(ex) => {}
-        //KismetExpression::ExArrayGetByRef(ex) => {}
+        KismetExpression::ExArrayGetByRef(ex) => ExArrayGetByRef { token: ex.token,
+            array_variable: Box::new(copy_expression(from, to, fn_from, fn_to, &ex.array_variable)),
+            array_index: Box::new(copy_expression(from, to, fn_from, fn_to, &ex.array_index)),
+        }.into(),
         //KismetExpression::ExClassSparseDataVariable(ex) => {}
-        //KismetExpression::ExFieldPathConst(ex) => {}
+        KismetExpression::ExFieldPathConst(ex) => ExFieldPathConst { token: ex.token,
+            value: Box::new(copy_expression(from, to, fn_from, fn_to, &ex.value)),
+        }.into(),
         _ => todo!("{:#?}", ex.get_token()),
     }
 }
@@ -739,6 +1548,118 @@ fn copy_package<C: std::io::Read + std::io::Seek>(
     package
 }
 
+/// full path of the object an index such as `ExObjectConst.value` refers to
+/// imports resolve to e.g. `/Script/Engine.Actor`, exports to a path relative to the asset's
+/// package e.g. `Bar_C:Component`
+/// `None` for the null index or one outside the import or export table
+pub fn resolve_object_const<C: std::io::Read + std::io::Seek>(
+    asset: &Asset<C>,
+    index: PackageIndex,
+) -> Option<String> {
+    if index.is_import() {
+        let import = asset.get_import(index)?;
+        let name = import.object_name.get_owned_content();
+        if import.outer_index.index == 0 {
+            return Some(name);
+        }
+        let outer = resolve_object_const(asset, import.outer_index)?;
+        let outer_is_package = asset
+            .get_import(import.outer_index)
+            .is_some_and(|outer| outer.outer_index.index == 0);
+        Some(if outer_is_package {
+            crate::paths::object_path(&outer, &name)
+        } else {
+            crate::paths::sub_object_path(&outer, &name)
+        })
+    } else if index.is_export() {
+        let export = asset.get_export(index)?.get_base_export();
+        let name = export.object_name.get_owned_content();
+        if export.outer_index.index == 0 {
+            return Some(name);
+        }
+        let outer = resolve_object_const(asset, export.outer_index)?;
+        Some(crate::paths::sub_object_path(&outer, &name))
+    } else {
+        None
+    }
+}
+
+/// object references held by `ex` and its subexpressions that [`resolve_object_const`] can't
+/// resolve in `asset`, such as an `ExObjectConst` past the end of the import table
+/// property pointer owners are not checked
+pub fn unresolved_references<C: std::io::Read + std::io::Seek>(
+    asset: &Asset<C>,
+    ex: &KismetExpression,
+) -> Vec<PackageIndex> {
+    let unresolved = RefCell::new(vec![]);
+    walk_ref(ex, &|ex| {
+        let index = match ex {
+            KismetExpression::ExObjectConst(ex) => ex.value,
+            KismetExpression::ExCallMath(ex) => ex.stack_node,
+            KismetExpression::ExFinalFunction(ex) => ex.stack_node,
+            KismetExpression::ExLocalFinalFunction(ex) => ex.stack_node,
+            KismetExpression::ExCallMulticastDelegate(ex) => ex.stack_node,
+            KismetExpression::ExDynamicCast(ex) => ex.class_ptr,
+            KismetExpression::ExMetaCast(ex) => ex.class_ptr,
+            KismetExpression::ExStructConst(ex) => ex.struct_value,
+            _ => return,
+        };
+        if index.index != 0 && resolve_object_const(asset, index).is_none() {
+            unresolved.borrow_mut().push(index);
+        }
+    });
+    unresolved.into_inner()
+}
+
+/// why [`resolve_object_const`] can't resolve `index`, naming the table it points into
+fn describe_unresolved<C: std::io::Read + std::io::Seek>(
+    asset: &Asset<C>,
+    index: PackageIndex,
+) -> String {
+    let (kind, i, len) = if index.is_import() {
+        ("import", -index.index - 1, asset.imports.len())
+    } else {
+        ("export", index.index - 1, asset.asset_data.exports.len())
+    };
+    if (i as usize) < len {
+        let name = ExprCtx { asset }.resolve_index(&index);
+        format!(
+            "{kind} {} {name:?} has an outer that does not resolve",
+            index.index
+        )
+    } else {
+        format!(
+            "{kind} {} is past the end of the {len} {kind}s",
+            index.index
+        )
+    }
+}
+
+/// [`copy_expression`] that first checks every object `ex` references resolves in `from`
+/// rather than panicking partway through the copy
+pub fn checked_copy_expression<C: std::io::Read + std::io::Seek>(
+    from: &Asset<C>,
+    to: &mut Asset<C>,
+    fn_from: PackageIndex,
+    fn_to: PackageIndex,
+    ex: &KismetExpression,
+) -> Result<KismetExpression> {
+    let unresolved = unresolved_references(from, ex);
+    if !unresolved.is_empty() {
+        let function = resolve_object_const(from, fn_from)
+            .unwrap_or_else(|| format!("function {}", fn_from.index));
+        let reasons: Vec<_> = unresolved
+            .into_iter()
+            .map(|pi| describe_unresolved(from, pi))
+            .collect();
+        anyhow::bail!(
+            "unresolved object references in {function}: {}",
+            reasons.join(", ")
+        );
+    }
+    Ok(copy_expression(from, to, fn_from, fn_to, ex))
+}
+
 fn to_tracked_statements(
     version: AssetVersion,
     origin: &(Option<String>, PackageIndex),
@@ -754,6 +1675,7 @@ fn to_tracked_statements(
                 origin: origin.clone(),
                 points_to: None,
                 original_offset: Some(oi),
+                merged_offsets: vec![],
                 ex,
             }
         })
@@ -881,27 +1803,284 @@ pub fn extract_tracked_statements<C: std::io::Read + std::io::Seek>(
             }),
     )
 }
-pub fn inject_tracked_statements<C: std::io::Read + std::io::Seek>(
-    asset: &mut Asset<C>,
-    version: AssetVersion,
-    mut statements: AssetInstructionMap,
-) {
-    let mut mapping = NamedSpliceMappings::new();
-    for (_pi, inst) in statements.iter_mut() {
-        let mut index = 0;
-        for inst in inst {
-            if let Some(oo) = inst.original_offset {
-                mapping
-                    .entry(inst.origin.clone())
-                    .or_default()
-                    .insert(oo, index);
-            }
-            let o = index;
-            index += get_size(&inst.ex, version).unwrap();
-            shift_switch(&mut inst.ex, o as i32);
+/// whether the bytecode ends with its only ExEndOfScript
+pub fn ends_with_end_of_script(exprs: &[KismetExpression]) -> bool {
+    let is_end = |ex: &KismetExpression| matches!(ex, KismetExpression::ExEndOfScript(_));
+    exprs.last().is_some_and(is_end) && exprs.iter().filter(|ex| is_end(ex)).count() == 1
+}
+/// append a statement, keeping ExEndOfScript last
+pub fn push_statement(statements: &mut Vec<TrackedStatement>, inst: TrackedStatement) {
+    let index = match statements.last() {
+        Some(last) if matches!(last.ex, KismetExpression::ExEndOfScript(_)) => statements.len() - 1,
+        _ => statements.len(),
+    };
+    statements.insert(index, inst);
+}
+/// leave exactly one ExEndOfScript, as the last statement
+/// one is appended if there is none, extras are removed with offsets of the same origin
+/// redirected to the kept terminator
+fn single_end_of_script(statements: &mut Vec<TrackedStatement>, function: PackageIndex) {
+    let origin = statements
+        .last()
+        .map_or((None, function), |inst| inst.origin.clone());
+    let (ends, rest): (Vec<_>, Vec<_>) = std::mem::take(statements)
+        .into_iter()
+        .partition(|inst| matches!(inst.ex, KismetExpression::ExEndOfScript(_)));
+    *statements = rest;
+    let mut ends = ends.into_iter();
+    let mut end = ends.next_back().unwrap_or_else(|| TrackedStatement {
+        origin,
+        points_to: None,
+        original_offset: None,
+        merged_offsets: vec![],
+        ex: ExEndOfScript {
+            token: EExprToken::ExEndOfScript,
         }
-    }
-
+        .into(),
+    });
+    for mut extra in ends {
+        if extra.origin == end.origin {
+            end.merged_offsets.extend(extra.original_offset);
+            end.merged_offsets.append(&mut extra.merged_offsets);
+        }
+    }
+    statements.push(end);
+}
+/// remove tracepoints, breakpoints and instrumentation events and clear `debug_mode` on asserts
+/// jumps to a removed statement land on the statement following it once injected
+pub fn strip_debug(statements: &mut Vec<TrackedStatement>) {
+    remove_statements(statements, |ex| {
+        matches!(
+            ex,
+            KismetExpression::ExTracepoint(_)
+                | KismetExpression::ExWireTracepoint(_)
+                | KismetExpression::ExBreakpoint(_)
+                | KismetExpression::ExInstrumentationEvent(_)
+        )
+    });
+    for inst in statements.iter_mut() {
+        walk(&mut inst.ex, &|ex| {
+            if let KismetExpression::ExAssert(ex) = ex {
+                ex.debug_mode = false;
+            }
+        });
+    }
+}
+/// conservative peephole pass over a function's statements
+/// jumps on a constant ExTrue/ExFalse condition are dropped or made unconditional and jumps
+/// landing on an unconditional jump are pointed at its target
+/// offsets are recomputed when the statements are injected
+pub fn optimize_bytecode(statements: &mut Vec<TrackedStatement>) {
+    let constant = |ex: &KismetExpression| match ex {
+        KismetExpression::ExTrue(_) => Some(true),
+        KismetExpression::ExFalse(_) => Some(false),
+        _ => None,
+    };
+
+    for inst in statements.iter_mut() {
+        let always_taken: Option<KismetExpression> = match &inst.ex {
+            KismetExpression::ExJumpIfNot(ex)
+                if constant(&ex.boolean_expression) == Some(false) =>
+            {
+                Some(
+                    ExJump {
+                        token: EExprToken::ExJump,
+                        code_offset: ex.code_offset,
+                    }
+                    .into(),
+                )
+            }
+            KismetExpression::ExPopExecutionFlowIfNot(ex)
+                if constant(&ex.boolean_expression) == Some(false) =>
+            {
+                Some(
+                    ExPopExecutionFlow {
+                        token: EExprToken::ExPopExecutionFlow,
+                    }
+                    .into(),
+                )
+            }
+            _ => None,
+        };
+        if let Some(ex) = always_taken {
+            inst.ex = ex;
+        }
+    }
+    remove_statements(statements, |ex| match ex {
+        KismetExpression::ExJumpIfNot(ex) => constant(&ex.boolean_expression) == Some(true),
+        KismetExpression::ExPopExecutionFlowIfNot(ex) => {
+            constant(&ex.boolean_expression) == Some(true)
+        }
+        _ => false,
+    });
+
+    let mut at_offset = HashMap::new();
+    for (i, inst) in statements.iter().enumerate() {
+        for offset in inst.original_offset.iter().chain(&inst.merged_offsets) {
+            at_offset.insert((&inst.origin, *offset), i);
+        }
+    }
+    let jump_target = |i: usize| match &statements[i].ex {
+        KismetExpression::ExJump(ex) if statements[i].points_to.is_none() => Some(ex.code_offset),
+        _ => None,
+    };
+    let mut threaded = vec![];
+    for (i, inst) in statements.iter().enumerate() {
+        let offset = match &inst.ex {
+            KismetExpression::ExJump(ex) => ex.code_offset,
+            KismetExpression::ExJumpIfNot(ex) => ex.code_offset,
+            _ => continue,
+        };
+        if inst.points_to.is_some() {
+            continue;
+        }
+        let mut target = offset;
+        // bounded so a cycle of jumps can't loop forever
+        for _ in 0..statements.len() {
+            match at_offset
+                .get(&(&inst.origin, target as usize))
+                .and_then(|&j| jump_target(j))
+            {
+                Some(next) if next != target => target = next,
+                _ => break,
+            }
+        }
+        if target != offset {
+            threaded.push((i, target));
+        }
+    }
+    for (i, target) in threaded {
+        match &mut statements[i].ex {
+            KismetExpression::ExJump(ex) => ex.code_offset = target,
+            KismetExpression::ExJumpIfNot(ex) => ex.code_offset = target,
+            _ => unreachable!(),
+        }
+    }
+}
+
+/// execution flow problem found by [`validate_execution_flow`], with the offending offset
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FlowError {
+    /// a pop can run with nothing pushed
+    PopEmpty(usize),
+    /// a jump or pushed address does not land on a statement
+    BadTarget(usize),
+    /// pushes can grow the stack without bound, e.g. a push inside a loop
+    Unbounded(usize),
+    /// the statement's size is unknown as it can't be written for the asset's version
+    Unsized(usize),
+}
+impl std::fmt::Display for FlowError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::PopEmpty(o) => write!(f, "execution flow popped while empty at {o}"),
+            Self::BadTarget(o) => write!(f, "jump at {o} does not land on a statement"),
+            Self::Unbounded(o) => write!(f, "execution flow grows without bound at {o}"),
+            Self::Unsized(o) => write!(f, "size of statement at {o} is unknown"),
+        }
+    }
+}
+impl std::error::Error for FlowError {}
+
+/// walk every path through a function's statements tracking the execution flow stack
+/// pops that can hit an empty stack are reported
+/// computed jumps end the path as their target is unknown
+pub fn validate_execution_flow(
+    version: AssetVersion,
+    exprs: &[KismetExpression],
+) -> Result<(), FlowError> {
+    let mut offsets = Vec::with_capacity(exprs.len());
+    let mut at_offset = HashMap::new();
+    let mut offset = 0;
+    for (i, ex) in exprs.iter().enumerate() {
+        offsets.push(offset);
+        at_offset.insert(offset, i);
+        offset += get_size(ex, version).map_err(|_| FlowError::Unsized(offset))?;
+    }
+    let target = |from: usize, to: u32| {
+        at_offset
+            .get(&(to as usize))
+            .copied()
+            .ok_or(FlowError::BadTarget(offsets[from]))
+    };
+
+    let mut visited = HashSet::new();
+    let mut queue = vec![(0, vec![])];
+    while let Some((i, mut stack)) = queue.pop() {
+        if i >= exprs.len() || !visited.insert((i, stack.clone())) {
+            continue;
+        }
+        if stack.len() > exprs.len() {
+            return Err(FlowError::Unbounded(offsets[i]));
+        }
+        match &exprs[i] {
+            KismetExpression::ExJump(ex) => queue.push((target(i, ex.code_offset)?, stack)),
+            KismetExpression::ExJumpIfNot(ex) => {
+                queue.push((target(i, ex.code_offset)?, stack.clone()));
+                queue.push((i + 1, stack));
+            }
+            KismetExpression::ExPushExecutionFlow(ex) => {
+                stack.push(target(i, ex.pushing_address)?);
+                queue.push((i + 1, stack));
+            }
+            KismetExpression::ExPopExecutionFlow(_) => {
+                let resume = stack.pop().ok_or(FlowError::PopEmpty(offsets[i]))?;
+                queue.push((resume, stack));
+            }
+            KismetExpression::ExPopExecutionFlowIfNot(ex) => {
+                queue.push((i + 1, stack.clone()));
+                let resume = stack.pop().ok_or(FlowError::PopEmpty(offsets[i]))?;
+                queue.push((resume, stack));
+            }
+            KismetExpression::ExReturn(_)
+            | KismetExpression::ExEndOfScript(_)
+            | KismetExpression::ExComputedJump(_) => {}
+            _ => queue.push((i + 1, stack)),
+        }
+    }
+    Ok(())
+}
+/// remove statements matching `remove`, merging their offsets into the following statement so
+/// jumps to them land there once injected
+fn remove_statements(
+    statements: &mut Vec<TrackedStatement>,
+    remove: impl Fn(&KismetExpression) -> bool,
+) {
+    let mut merged = vec![];
+    let mut kept = Vec::with_capacity(statements.len());
+    let mut iter = std::mem::take(statements).into_iter().peekable();
+    while let Some(mut inst) = iter.next() {
+        // offsets can only be redirected to a following statement of the same origin
+        if remove(&inst.ex) && iter.peek().is_some_and(|next| next.origin == inst.origin) {
+            merged.extend(inst.original_offset);
+            merged.append(&mut inst.merged_offsets);
+            continue;
+        }
+        inst.merged_offsets.append(&mut merged);
+        kept.push(inst);
+    }
+    *statements = kept;
+}
+pub fn inject_tracked_statements<C: std::io::Read + std::io::Seek>(
+    asset: &mut Asset<C>,
+    version: AssetVersion,
+    mut statements: AssetInstructionMap,
+) {
+    let mut mapping = NamedSpliceMappings::new();
+    for (pi, inst) in statements.iter_mut() {
+        single_end_of_script(inst, *pi);
+        let mut index = 0;
+        for inst in inst {
+            let offsets = mapping.entry(inst.origin.clone()).or_default();
+            for oo in inst.original_offset.iter().chain(&inst.merged_offsets) {
+                offsets.insert(*oo, index);
+            }
+            let o = index;
+            index += get_size(&inst.ex, version).unwrap();
+            shift_switch(&mut inst.ex, o as i32);
+        }
+    }
+
     for (pi, statements) in statements.into_iter() {
         let bytecode = resolve_tracked_statements(asset, &mapping, statements);
         if let Some(Export::FunctionExport(f)) = asset.get_export_mut(pi) {
@@ -1064,3 +2243,1225 @@ pub fn find_hooks<'a, C: std::io::Read + std::io::Seek>(
     }
     hooks
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use unreal_asset::kismet::{ExAssert, ExFinalFunction, ExTracepoint, ExUnicodeStringConst};
+
+    #[test]
+    fn string_constants() {
+        use unreal_asset::kismet::{EBlueprintTextLiteralType, FScriptText};
+        let string = |value: &str| -> KismetExpression {
+            ExStringConst {
+                token: EExprToken::ExStringConst,
+                value: value.to_string(),
+            }
+            .into()
+        };
+        let text = ExTextConst {
+            token: EExprToken::ExTextConst,
+            value: Box::new(FScriptText {
+                text_literal_type: EBlueprintTextLiteralType::LocalizedText,
+                localized_source: Some(string("source")),
+                localized_key: Some(string("key")),
+                localized_namespace: Some(string("namespace")),
+                invariant_literal_string: None,
+                literal_string: None,
+                string_table_asset: None,
+                string_table_id: None,
+                string_table_key: None,
+            }),
+        };
+        let ex: KismetExpression = ExCallMath {
+            token: EExprToken::ExCallMath,
+            stack_node: PackageIndex::new(-1),
+            parameters: vec![
+                string("ansi"),
+                ExUnicodeStringConst {
+                    token: EExprToken::ExUnicodeStringConst,
+                    value: "Näme".to_string(),
+                }
+                .into(),
+                ExReturn {
+                    token: EExprToken::ExReturn,
+                    return_expression: Box::new(string("nested")),
+                }
+                .into(),
+                text.into(),
+            ],
+        }
+        .into();
+        assert_eq!(
+            collect_string_constants(&ex),
+            ["ansi", "Näme", "nested", "source"]
+        );
+    }
+
+    #[test]
+    fn strip_debug_redirects_jumps() {
+        let origin = (None, PackageIndex::new(1));
+        let inst = |offset: usize, ex: KismetExpression| TrackedStatement {
+            origin: origin.clone(),
+            points_to: None,
+            original_offset: Some(offset),
+            merged_offsets: vec![],
+            ex,
+        };
+        let tracepoint = || -> KismetExpression {
+            ExTracepoint {
+                token: EExprToken::ExTracepoint,
+            }
+            .into()
+        };
+        let mut statements = vec![
+            inst(
+                0,
+                ExJump {
+                    token: EExprToken::ExJump,
+                    code_offset: 5,
+                }
+                .into(),
+            ),
+            inst(5, tracepoint()),
+            inst(6, tracepoint()),
+            inst(
+                7,
+                ExAssert {
+                    token: EExprToken::ExAssert,
+                    line_number: 1,
+                    debug_mode: true,
+                    assert_expression: Box::new(
+                        ExTrue {
+                            token: EExprToken::ExTrue,
+                        }
+                        .into(),
+                    ),
+                }
+                .into(),
+            ),
+        ];
+        strip_debug(&mut statements);
+
+        assert_eq!(statements.len(), 2);
+        let KismetExpression::ExJump(jump) = &statements[0].ex else {
+            panic!("expected jump");
+        };
+        let target = &statements[1];
+        assert!(target.merged_offsets.contains(&(jump.code_offset as usize)));
+        assert_eq!(target.merged_offsets, [5, 6]);
+        let KismetExpression::ExAssert(assert) = &target.ex else {
+            panic!("expected assert");
+        };
+        assert!(!assert.debug_mode);
+    }
+
+    #[test]
+    fn push_keeps_end_of_script_last() {
+        let inst = |ex: KismetExpression| TrackedStatement {
+            origin: (None, PackageIndex::new(1)),
+            points_to: None,
+            original_offset: None,
+            merged_offsets: vec![],
+            ex,
+        };
+        let call = || -> KismetExpression {
+            ExCallMath {
+                token: EExprToken::ExCallMath,
+                stack_node: PackageIndex::new(-1),
+                parameters: vec![],
+            }
+            .into()
+        };
+        let mut statements = vec![
+            inst(call()),
+            inst(
+                ExEndOfScript {
+                    token: EExprToken::ExEndOfScript,
+                }
+                .into(),
+            ),
+        ];
+        push_statement(&mut statements, inst(call()));
+
+        let exprs: Vec<_> = statements.into_iter().map(|inst| inst.ex).collect();
+        assert_eq!(exprs.len(), 3);
+        assert!(ends_with_end_of_script(&exprs));
+        assert!(!ends_with_end_of_script(&exprs[..2]));
+    }
+
+    #[test]
+    fn single_end_of_script() {
+        let end = || -> KismetExpression {
+            ExEndOfScript {
+                token: EExprToken::ExEndOfScript,
+            }
+            .into()
+        };
+        let exprs = |statements: &[TrackedStatement]| -> Vec<KismetExpression> {
+            statements.iter().map(|inst| inst.ex.clone()).collect()
+        };
+
+        // missing terminator is appended
+        let mut statements = vec![statement(0, nothing())];
+        super::single_end_of_script(&mut statements, PackageIndex::new(1));
+        assert_eq!(statements.len(), 2);
+        assert!(ends_with_end_of_script(&exprs(&statements)));
+        let mut statements = vec![];
+        super::single_end_of_script(&mut statements, PackageIndex::new(1));
+        assert!(ends_with_end_of_script(&exprs(&statements)));
+        assert_eq!(statements[0].origin, (None, PackageIndex::new(1)));
+
+        // extras collapse into the last, which takes over their offsets
+        let mut statements = vec![
+            statement(0, end()),
+            statement(1, nothing()),
+            statement(2, end()),
+            statement(3, nothing()),
+        ];
+        super::single_end_of_script(&mut statements, PackageIndex::new(1));
+        assert_eq!(statements.len(), 3);
+        assert!(ends_with_end_of_script(&exprs(&statements)));
+        let last = statements.last().unwrap();
+        assert_eq!(last.original_offset, Some(2));
+        assert_eq!(last.merged_offsets, [0]);
+    }
+
+    #[test]
+    fn self_call() {
+        let version = version();
+        let call: KismetExpression = ExFinalFunction {
+            token: EExprToken::ExFinalFunction,
+            stack_node: PackageIndex::new(-1),
+            parameters: vec![],
+        }
+        .into();
+        let call_size = get_size(&call, version).unwrap();
+        let ex = context_expression(
+            version,
+            self_expression(),
+            // no return value
+            prop(0),
+            call,
+        )
+        .unwrap();
+
+        let KismetExpression::ExContext(context) = &ex else {
+            panic!("expected context");
+        };
+        assert!(matches!(
+            *context.object_expression,
+            KismetExpression::ExSelf(_)
+        ));
+        assert_eq!(context.offset as usize, call_size);
+        assert!(get_size(&ex, version).unwrap() > call_size);
+    }
+
+    #[test]
+    fn field_path_const() {
+        let version = version();
+        let owner: KismetExpression = ExObjectConst {
+            token: EExprToken::ExObjectConst,
+            value: PackageIndex::new(-1),
+        }
+        .into();
+        let owner_size = get_size(&owner, version).unwrap();
+        let mut ex: KismetExpression = ExFieldPathConst {
+            token: EExprToken::ExFieldPathConst,
+            value: Box::new(owner),
+        }
+        .into();
+        assert_eq!(get_size(&ex, version).unwrap(), 1 + owner_size);
+
+        walk(&mut ex, &|ex| {
+            if let KismetExpression::ExObjectConst(ex) = ex {
+                ex.value = PackageIndex::new(-2);
+            }
+        });
+        let KismetExpression::ExFieldPathConst(field_path) = &ex else {
+            panic!("expected field path const");
+        };
+        assert!(matches!(
+            &*field_path.value,
+            KismetExpression::ExObjectConst(owner) if owner.value == PackageIndex::new(-2)
+        ));
+
+        // the owner is remapped into the destination's import table
+        let (to, ex) = copy_actor_reference(|actor| {
+            ExFieldPathConst {
+                token: EExprToken::ExFieldPathConst,
+                value: Box::new(object_expression(actor)),
+            }
+            .into()
+        });
+        let KismetExpression::ExFieldPathConst(field_path) = &ex else {
+            panic!("expected field path const");
+        };
+        let KismetExpression::ExObjectConst(owner) = &*field_path.value else {
+            panic!("expected object const owner");
+        };
+        assert_eq!(owner.value, PackageIndex::new(-3));
+        assert_eq!(
+            super::resolve_object_const(&to, owner.value).as_deref(),
+            Some("/Script/Engine.Actor")
+        );
+    }
+
+    #[test]
+    fn literal_constructors() {
+        let version = version();
+        let bytes = |ex: &KismetExpression| {
+            use unreal_asset::kismet::KismetExpressionTrait;
+            let mut buf = Cursor::new(vec![]);
+            let mut writer = unreal_asset::reader::raw_writer::RawWriter::new(
+                &mut buf,
+                version.version,
+                version.version_ue5,
+                false,
+                NameMap::new(),
+            );
+            ex.write(&mut writer).unwrap();
+            buf.into_inner()
+        };
+
+        let ex = int_expression(-2);
+        assert!(matches!(&ex, KismetExpression::ExIntConst(ex) if ex.value == -2));
+        assert_eq!(bytes(&ex), (-2i32).to_le_bytes());
+
+        let ex = float_expression(1.5);
+        assert!(matches!(ex, KismetExpression::ExFloatConst(_)));
+        assert_eq!(bytes(&ex), 1.5f32.to_le_bytes());
+
+        let ex = string_expression("hi");
+        assert!(matches!(&ex, KismetExpression::ExStringConst(ex) if ex.value == "hi"));
+        assert_eq!(bytes(&ex), b"hi\0");
+
+        let ex = object_expression(PackageIndex::new(-3));
+        assert!(
+            matches!(&ex, KismetExpression::ExObjectConst(ex) if ex.value == PackageIndex::new(-3))
+        );
+        assert_eq!(bytes(&ex), (-3i32).to_le_bytes());
+
+        assert!(matches!(bool_expression(true), KismetExpression::ExTrue(_)));
+        assert!(matches!(
+            bool_expression(false),
+            KismetExpression::ExFalse(_)
+        ));
+        assert!(bytes(&bool_expression(true)).is_empty());
+        assert!(matches!(
+            nothing_expression(),
+            KismetExpression::ExNothing(_)
+        ));
+        assert!(bytes(&nothing_expression()).is_empty());
+
+        assert!(matches!(
+            name_expression(FName::new_dummy("Foo".to_string(), 0)),
+            KismetExpression::ExNameConst(_)
+        ));
+    }
+
+    #[test]
+    fn execution_flow() {
+        let version = version();
+        let pop: KismetExpression = ExPopExecutionFlow {
+            token: EExprToken::ExPopExecutionFlow,
+        }
+        .into();
+        let end: KismetExpression = ExEndOfScript {
+            token: EExprToken::ExEndOfScript,
+        }
+        .into();
+        let push = |pushing_address: usize| -> KismetExpression {
+            ExPushExecutionFlow {
+                token: EExprToken::ExPushExecutionFlow,
+                pushing_address: pushing_address as u32,
+            }
+            .into()
+        };
+
+        // push the end, run the body, pop back to the end
+        let push_size = get_size(&push(0), version).unwrap();
+        let pop_size = get_size(&pop, version).unwrap();
+        let nothing: KismetExpression = nothing().into();
+        let nothing_size = get_size(&nothing, version).unwrap();
+        let balanced = [
+            push(push_size + nothing_size + pop_size),
+            nothing.clone(),
+            pop.clone(),
+            end.clone(),
+        ];
+        assert_eq!(validate_execution_flow(version, &balanced), Ok(()));
+
+        let unbalanced = [nothing, pop, end];
+        assert_eq!(
+            validate_execution_flow(version, &unbalanced),
+            Err(FlowError::PopEmpty(nothing_size))
+        );
+    }
+
+    #[test]
+    fn rebase_jump() {
+        let exprs: [KismetExpression; 3] = [
+            nothing().into(),
+            jump_if_not(9, true).into(),
+            jump(0).into(),
+        ];
+        let rebased = rebase_function(&exprs, 100);
+        assert!(matches!(rebased[0], KismetExpression::ExNothing(_)));
+        assert!(matches!(&rebased[1], KismetExpression::ExJumpIfNot(ex) if ex.code_offset == 109));
+        assert!(matches!(&rebased[2], KismetExpression::ExJump(ex) if ex.code_offset == 100));
+    }
+
+    #[test]
+    fn primitive_cast() {
+        use unreal_asset::kismet::ECastToken;
+        let version = version();
+        let target = object_expression(PackageIndex::new(-1));
+        let target_size = get_size(&target, version).unwrap();
+        let mut ex: KismetExpression = ExPrimitiveCast {
+            token: EExprToken::ExPrimitiveCast,
+            conversion_type: ECastToken::ObjectToBool,
+            target: Box::new(target),
+        }
+        .into();
+        // token, cast token, target
+        assert_eq!(get_size(&ex, version).unwrap(), 2 + target_size);
+
+        walk(&mut ex, &|ex| {
+            if let KismetExpression::ExObjectConst(ex) = ex {
+                ex.value = PackageIndex::new(-2);
+            }
+        });
+        let KismetExpression::ExPrimitiveCast(cast) = &ex else {
+            panic!("expected primitive cast");
+        };
+        assert_eq!(cast.conversion_type, ECastToken::ObjectToBool);
+        assert!(matches!(
+            &*cast.target,
+            KismetExpression::ExObjectConst(target) if target.value == PackageIndex::new(-2)
+        ));
+
+        // the cast type survives a copy between assets while the target is remapped
+        let (to, ex) = copy_actor_reference(|actor| {
+            ExPrimitiveCast {
+                token: EExprToken::ExPrimitiveCast,
+                conversion_type: ECastToken::ObjectToBool,
+                target: Box::new(object_expression(actor)),
+            }
+            .into()
+        });
+        let KismetExpression::ExPrimitiveCast(cast) = &ex else {
+            panic!("expected primitive cast");
+        };
+        assert_eq!(cast.conversion_type, ECastToken::ObjectToBool);
+        let KismetExpression::ExObjectConst(target) = &*cast.target else {
+            panic!("expected object const target");
+        };
+        assert_eq!(target.value, PackageIndex::new(-3));
+        assert_eq!(
+            super::resolve_object_const(&to, target.value).as_deref(),
+            Some("/Script/Engine.Actor")
+        );
+    }
+
+    #[test]
+    fn array_struct_member_access() {
+        // Local.Member[1]
+        let mut ex: KismetExpression = ExArrayGetByRef {
+            token: EExprToken::ExArrayGetByRef,
+            array_variable: Box::new(
+                ExStructMemberContext {
+                    token: EExprToken::ExStructMemberContext,
+                    struct_member_expression: prop(-1),
+                    struct_expression: Box::new(
+                        ExLocalVariable {
+                            token: EExprToken::ExLocalVariable,
+                            variable: prop(-2),
+                        }
+                        .into(),
+                    ),
+                }
+                .into(),
+            ),
+            array_index: Box::new(int_expression(1)),
+        }
+        .into();
+
+        let visited = RefCell::new(vec![]);
+        walk(&mut ex, &|ex| visited.borrow_mut().push(ex.get_token()));
+        assert_eq!(
+            visited.into_inner(),
+            [
+                EExprToken::ExArrayGetByRef,
+                EExprToken::ExStructMemberContext,
+                EExprToken::ExLocalVariable,
+                EExprToken::ExIntConst,
+            ]
+        );
+
+        let owners = RefCell::new(vec![]);
+        walk_property_pointers(&mut ex, &|p| {
+            let path = p.new.as_mut().unwrap();
+            owners.borrow_mut().push(path.resolved_owner.index);
+            path.resolved_owner = PackageIndex::new(path.resolved_owner.index - 10);
+        });
+        assert_eq!(owners.into_inner(), [-1, -2]);
+
+        let KismetExpression::ExArrayGetByRef(get) = &ex else {
+            unreachable!()
+        };
+        let KismetExpression::ExStructMemberContext(member) = &*get.array_variable else {
+            unreachable!()
+        };
+        let owner = |p: &KismetPropertyPointer| p.new.as_ref().unwrap().resolved_owner;
+        assert_eq!(
+            owner(&member.struct_member_expression),
+            PackageIndex::new(-11)
+        );
+        let KismetExpression::ExLocalVariable(local) = &*member.struct_expression else {
+            unreachable!()
+        };
+        assert_eq!(owner(&local.variable), PackageIndex::new(-12));
+
+        // property paths survive a copy between assets while their owners are remapped
+        let named = |name: &str, owner: PackageIndex| {
+            let mut pointer = prop(owner.index);
+            pointer.new.as_mut().unwrap().path = vec![FName::new_dummy(name.to_string(), 0)];
+            pointer
+        };
+        let (_, ex) = copy_actor_reference(|actor| {
+            ExArrayGetByRef {
+                token: EExprToken::ExArrayGetByRef,
+                array_variable: Box::new(
+                    ExStructMemberContext {
+                        token: EExprToken::ExStructMemberContext,
+                        struct_member_expression: named("Member", actor),
+                        struct_expression: Box::new(
+                            ExLocalVariable {
+                                token: EExprToken::ExLocalVariable,
+                                variable: named("Local", actor),
+                            }
+                            .into(),
+                        ),
+                    }
+                    .into(),
+                ),
+                array_index: Box::new(int_expression(1)),
+            }
+            .into()
+        });
+        let KismetExpression::ExArrayGetByRef(get) = &ex else {
+            panic!("expected array get by ref");
+        };
+        assert!(matches!(&*get.array_index, KismetExpression::ExIntConst(ex) if ex.value == 1));
+        let KismetExpression::ExStructMemberContext(member) = &*get.array_variable else {
+            panic!("expected struct member context");
+        };
+        let KismetExpression::ExLocalVariable(local) = &*member.struct_expression else {
+            panic!("expected local variable");
+        };
+        for (pointer, name) in [
+            (&member.struct_member_expression, "Member"),
+            (&local.variable, "Local"),
+        ] {
+            let path = pointer.new.as_ref().unwrap();
+            let names: Vec<_> = path.path.iter().map(|n| n.get_owned_content()).collect();
+            assert_eq!(names, [name]);
+            assert_eq!(path.resolved_owner, PackageIndex::new(-3));
+        }
+    }
+
+    #[test]
+    fn remap_package_indexes() {
+        let mut ex: KismetExpression = ExFinalFunction {
+            token: EExprToken::ExFinalFunction,
+            stack_node: PackageIndex::new(-1),
+            parameters: vec![
+                object_expression(PackageIndex::new(-2)),
+                ExLocalVariable {
+                    token: EExprToken::ExLocalVariable,
+                    variable: prop(-3),
+                }
+                .into(),
+                object_expression(PackageIndex::new(-4)),
+            ],
+        }
+        .into();
+
+        let map: HashMap<_, _> = (1..=3)
+            .map(|i| (PackageIndex::new(-i), PackageIndex::new(-10 * i)))
+            .collect();
+        walk_package_indexes(&mut ex, &|pi| {
+            if let Some(new) = map.get(pi) {
+                *pi = *new;
+            }
+        });
+
+        let seen = RefCell::new(vec![]);
+        walk_package_indexes(&mut ex, &|pi| seen.borrow_mut().push(pi.index));
+        assert_eq!(seen.into_inner(), [-10, -20, -4, -30]);
+    }
+
+    #[test]
+    fn remap_script_imports() {
+        use unreal_asset::kismet::{EBlueprintTextLiteralType, FScriptText};
+        let string_table: KismetExpression = ExTextConst {
+            token: EExprToken::ExTextConst,
+            value: Box::new(FScriptText {
+                text_literal_type: EBlueprintTextLiteralType::StringTableEntry,
+                localized_source: None,
+                localized_key: None,
+                localized_namespace: None,
+                invariant_literal_string: None,
+                literal_string: None,
+                string_table_asset: Some(PackageIndex::new(-2)),
+                string_table_id: Some(string_expression("Table")),
+                string_table_key: Some(string_expression("Key")),
+            }),
+        }
+        .into();
+        let call: KismetExpression = ExCallMath {
+            token: EExprToken::ExCallMath,
+            stack_node: PackageIndex::new(-1),
+            parameters: vec![object_expression(PackageIndex::new(-2)), string_table],
+        }
+        .into();
+        let mut asset = crate::test_util::empty_asset();
+        let class = crate::test_util::add_export(
+            &mut asset,
+            PackageIndex::new(-1),
+            PackageIndex::new(0),
+            "Bar_C",
+        );
+        let function = add_function(&mut asset, class, "Tick", vec![call]);
+
+        let map = HashMap::from([
+            (PackageIndex::new(-1), PackageIndex::new(-3)),
+            (PackageIndex::new(-2), PackageIndex::new(-4)),
+        ]);
+        super::remap_script_imports(&mut asset, &map);
+
+        // only bytecode is remapped, not the export table
+        let class = asset.get_export(class).unwrap().get_base_export();
+        assert_eq!(class.class_index, PackageIndex::new(-1));
+        let Some(Export::FunctionExport(f)) = asset.get_export(function) else {
+            panic!("expected function export");
+        };
+        let seen = RefCell::new(vec![]);
+        walk_ref(
+            &f.struct_export.script_bytecode.as_ref().unwrap()[0],
+            &|ex| match ex {
+                KismetExpression::ExCallMath(ex) => seen.borrow_mut().push(ex.stack_node),
+                KismetExpression::ExObjectConst(ex) => seen.borrow_mut().push(ex.value),
+                KismetExpression::ExTextConst(ex) => {
+                    seen.borrow_mut().extend(ex.value.string_table_asset)
+                }
+                _ => {}
+            },
+        );
+        assert_eq!(seen.into_inner(), [-3, -4, -4].map(PackageIndex::new));
+    }
+
+    #[test]
+    fn deprecated_ops() {
+        use unreal_asset::kismet::ExDeprecatedOp4A;
+        let version = version();
+        let deprecated: KismetExpression = ExDeprecatedOp4A {
+            token: EExprToken::ExDeprecatedOp4A,
+        }
+        .into();
+        let exprs = [int_expression(1), deprecated, nothing_expression()];
+        let offset = get_size(&exprs[0], version).unwrap() as u32;
+
+        let report = parse_report(version, &exprs).unwrap();
+        assert_eq!(
+            report.deprecated_ops,
+            [(offset, EExprToken::ExDeprecatedOp4A)]
+        );
+        assert_eq!(
+            parse_report(version, &exprs[..1]).unwrap(),
+            ParseReport::default()
+        );
+    }
+
+    #[test]
+    fn skip_offsets() {
+        use unreal_asset::kismet::ExSkip;
+        let version = version();
+        let context =
+            context_expression(version, self_expression(), prop(0), string_expression("a"))
+                .unwrap();
+        let mut ex: KismetExpression = ExSkip {
+            token: EExprToken::ExSkip,
+            code_offset: get_size(&context, version).unwrap() as u32,
+            skip_expression: Box::new(context),
+        }
+        .into();
+
+        // grow the innermost expression, invalidating both skip sizes
+        walk(&mut ex, &|ex| {
+            if let KismetExpression::ExStringConst(ex) = ex {
+                ex.value = "a longer string".to_string();
+            }
+        });
+        recompute_skip_offsets(version, &mut ex).unwrap();
+
+        let KismetExpression::ExSkip(skip) = &ex else {
+            unreachable!()
+        };
+        let KismetExpression::ExContext(context) = &*skip.skip_expression else {
+            unreachable!()
+        };
+        assert_eq!(
+            context.offset as usize,
+            get_size(&string_expression("a longer string"), version).unwrap()
+        );
+        assert_eq!(
+            skip.code_offset as usize,
+            get_size(&skip.skip_expression, version).unwrap()
+        );
+    }
+
+    #[test]
+    fn walk_delegate_ops() {
+        use unreal_asset::kismet::{
+            ExAddMulticastDelegate, ExBindDelegate, ExClearMulticastDelegate, ExLetDelegate,
+            ExLetMulticastDelegate, ExLetValueOnPersistentFrame, ExRemoveMulticastDelegate,
+        };
+        // each child is a distinct object reference so visits can be told apart
+        let child = |i: i32| Box::new(object_expression(PackageIndex::new(-i)));
+        let exprs: Vec<(KismetExpression, usize)> = vec![
+            (
+                ExAddMulticastDelegate {
+                    token: EExprToken::ExAddMulticastDelegate,
+                    delegate: child(1),
+                    delegate_to_add: child(2),
+                }
+                .into(),
+                2,
+            ),
+            (
+                ExRemoveMulticastDelegate {
+                    token: EExprToken::ExRemoveMulticastDelegate,
+                    delegate: child(1),
+                    delegate_to_add: child(2),
+                }
+                .into(),
+                2,
+            ),
+            (
+                ExClearMulticastDelegate {
+                    token: EExprToken::ExClearMulticastDelegate,
+                    delegate_to_clear: child(1),
+                }
+                .into(),
+                1,
+            ),
+            (
+                ExBindDelegate {
+                    token: EExprToken::ExBindDelegate,
+                    function_name: FName::new_dummy("Handler".to_string(), 0),
+                    delegate: child(1),
+                    object_term: child(2),
+                }
+                .into(),
+                2,
+            ),
+            (
+                ExLetDelegate {
+                    token: EExprToken::ExLetDelegate,
+                    variable_expression: child(1),
+                    assignment_expression: child(2),
+                }
+                .into(),
+                2,
+            ),
+            (
+                ExLetMulticastDelegate {
+                    token: EExprToken::ExLetMulticastDelegate,
+                    variable_expression: child(1),
+                    assignment_expression: child(2),
+                }
+                .into(),
+                2,
+            ),
+            (
+                ExLetValueOnPersistentFrame {
+                    token: EExprToken::ExLetValueOnPersistentFrame,
+                    destination_property: prop(0),
+                    assignment_expression: child(1),
+                }
+                .into(),
+                1,
+            ),
+            (
+                ExLet {
+                    token: EExprToken::ExLet,
+                    value: prop(0),
+                    variable: child(1),
+                    expression: child(2),
+                }
+                .into(),
+                2,
+            ),
+        ];
+        for (mut ex, children) in exprs {
+            let visited = RefCell::new(vec![]);
+            walk(&mut ex, &|ex| {
+                if let KismetExpression::ExObjectConst(ex) = ex {
+                    visited.borrow_mut().push(-ex.value.index);
+                }
+            });
+            let expected: Vec<i32> = (1..=children as i32).collect();
+            assert_eq!(visited.into_inner(), expected, "{:?}", ex.get_token());
+        }
+    }
+
+    #[test]
+    fn assemble_function() {
+        let version = version();
+        let exprs = assemble(
+            version,
+            r#"
+            # skip the call when false
+            JumpIfNot end False
+            CallMath -1 (Int 3, String "a \"b\"", Self)
+            end:
+            Return Nothing
+            EndOfScript
+            "#,
+        )
+        .unwrap();
+        assert_eq!(exprs.len(), 4);
+
+        let KismetExpression::ExCallMath(call) = &exprs[1] else {
+            panic!("expected CallMath");
+        };
+        assert_eq!(call.stack_node, PackageIndex::new(-1));
+        assert!(matches!(&call.parameters[0], KismetExpression::ExIntConst(ex) if ex.value == 3));
+        assert!(
+            matches!(&call.parameters[1], KismetExpression::ExStringConst(ex) if ex.value == "a \"b\"")
+        );
+        assert!(matches!(call.parameters[2], KismetExpression::ExSelf(_)));
+
+        let end = get_size(&exprs[0], version).unwrap() + get_size(&exprs[1], version).unwrap();
+        assert!(matches!(
+            &exprs[0],
+            KismetExpression::ExJumpIfNot(ex) if ex.code_offset as usize == end
+        ));
+        assert!(matches!(exprs[2], KismetExpression::ExReturn(_)));
+        assert!(matches!(exprs[3], KismetExpression::ExEndOfScript(_)));
+
+        assert!(assemble(version, "Jump nowhere").is_err());
+        assert!(assemble(version, "Int").is_err());
+        assert!(assemble(version, "Nothing Nothing").is_err());
+    }
+
+    #[test]
+    fn insert_after_call() {
+        let version = version();
+        let call = |stack_node: i32| -> KismetExpression {
+            ExFinalFunction {
+                token: EExprToken::ExFinalFunction,
+                stack_node: PackageIndex::new(stack_node),
+                parameters: vec![],
+            }
+            .into()
+        };
+        let end: KismetExpression = ExEndOfScript {
+            token: EExprToken::ExEndOfScript,
+        }
+        .into();
+        let jump_size = get_size(&jump_if_not(0, true).into(), version).unwrap();
+        let call_size = get_size(&call(-5), version).unwrap();
+        let nothing_size = get_size(&nothing_expression(), version).unwrap();
+
+        // skip both calls, landing on the end
+        let end_offset = jump_size + 2 * call_size;
+        let mut exprs = vec![
+            jump_if_not(end_offset as u32, false).into(),
+            call(-5),
+            call(-6),
+            end,
+        ];
+        let is_target = |ex: &KismetExpression| matches!(ex, KismetExpression::ExFinalFunction(ex) if ex.stack_node == PackageIndex::new(-5));
+        assert_eq!(find_pattern(&exprs, is_target), [1]);
+
+        let count =
+            insert_after_matching(version, &mut exprs, is_target, vec![nothing_expression()])
+                .unwrap();
+        assert_eq!(count, 1);
+        assert_eq!(exprs.len(), 5);
+        assert!(matches!(exprs[2], KismetExpression::ExNothing(_)));
+        assert!(matches!(
+            &exprs[0],
+            KismetExpression::ExJumpIfNot(ex) if ex.code_offset as usize == end_offset + nothing_size
+        ));
+
+        assert_eq!(
+            insert_after_matching(version, &mut exprs, |_| false, vec![nothing_expression()])
+                .unwrap(),
+            0
+        );
+        assert_eq!(exprs.len(), 5);
+    }
+
+    #[test]
+    fn offsets() {
+        let version = version();
+        let call: KismetExpression = ExFinalFunction {
+            token: EExprToken::ExFinalFunction,
+            stack_node: PackageIndex::new(-5),
+            parameters: vec![int_expression(1), int_expression(2)],
+        }
+        .into();
+        let exprs = [call.clone(), nothing_expression()];
+
+        let mut offsets = vec![];
+        walk_with_offsets(version, &exprs, |offset, ex| {
+            offsets.push((offset, ex.get_token()))
+        })
+        .unwrap();
+
+        let call_size = get_size(&call, version).unwrap() as u32;
+        let int_size = get_size(&int_expression(1), version).unwrap() as u32;
+        assert_eq!(
+            offsets,
+            [
+                (0, EExprToken::ExFinalFunction),
+                (1 + POINTER_SIZE as u32, EExprToken::ExIntConst),
+                (1 + POINTER_SIZE as u32 + int_size, EExprToken::ExIntConst),
+                (call_size, EExprToken::ExNothing),
+            ]
+        );
+        // the call ends with a one byte EX_EndFunctionParms
+        assert_eq!(call_size, 1 + POINTER_SIZE as u32 + 2 * int_size + 1);
+    }
+
+    #[test]
+    fn opcode_histogram() {
+        let end = || -> KismetExpression {
+            ExEndOfScript {
+                token: EExprToken::ExEndOfScript,
+            }
+            .into()
+        };
+        let call = || -> KismetExpression {
+            ExCallMath {
+                token: EExprToken::ExCallMath,
+                stack_node: PackageIndex::new(-1),
+                parameters: vec![int_expression(1), int_expression(2)],
+            }
+            .into()
+        };
+        let ret = || -> KismetExpression {
+            ExReturn {
+                token: EExprToken::ExReturn,
+                return_expression: Box::new(int_expression(3)),
+            }
+            .into()
+        };
+        let histogram = |functions: &[Vec<KismetExpression>]| {
+            let mut asset = crate::test_util::empty_asset();
+            for (i, bytecode) in functions.iter().enumerate() {
+                add_function(
+                    &mut asset,
+                    PackageIndex::new(0),
+                    &format!("F{i}"),
+                    bytecode.clone(),
+                );
+            }
+            super::opcode_histogram(&asset)
+        };
+        let total = |histogram: &BTreeMap<u8, usize>| histogram.values().sum::<usize>();
+
+        let a = vec![call(), end()];
+        let b = vec![ret(), end()];
+        let both = histogram(&[a.clone(), b.clone()]);
+        assert_eq!(
+            total(&both),
+            total(&histogram(&[a])) + total(&histogram(&[b]))
+        );
+        assert_eq!(total(&both), 7);
+        assert_eq!(both[&(EExprToken::ExIntConst as u8)], 3);
+        assert_eq!(both[&(EExprToken::ExEndOfScript as u8)], 2);
+    }
+
+    #[test]
+    fn resolve_object_const() {
+        use crate::test_util::{add_import, empty_asset};
+        let mut asset = empty_asset();
+        let engine = add_import(
+            &mut asset,
+            "/Script/CoreUObject",
+            "Package",
+            PackageIndex::new(0),
+            "/Script/Engine",
+        );
+        let actor = add_import(&mut asset, "/Script/CoreUObject", "Class", engine, "Actor");
+        let function = add_function(&mut asset, PackageIndex::new(0), "Tick", vec![]);
+
+        let resolve = |index: PackageIndex| super::resolve_object_const(&asset, index);
+        assert_eq!(resolve(engine).as_deref(), Some("/Script/Engine"));
+        assert_eq!(resolve(actor).as_deref(), Some("/Script/Engine.Actor"));
+        assert_eq!(resolve(function).as_deref(), Some("Tick"));
+        assert_eq!(resolve(PackageIndex::new(0)), None);
+        assert_eq!(resolve(PackageIndex::new(-3)), None);
+        assert_eq!(resolve(PackageIndex::new(2)), None);
+    }
+
+    #[test]
+    fn checked_copy_expression() {
+        use crate::test_util::{add_import, empty_asset};
+        let mut from = empty_asset();
+        let engine = add_import(
+            &mut from,
+            "/Script/CoreUObject",
+            "Package",
+            PackageIndex::new(0),
+            "/Script/Engine",
+        );
+        let actor = add_import(&mut from, "/Script/CoreUObject", "Class", engine, "Actor");
+        let fn_from = add_function(&mut from, PackageIndex::new(0), "Tick", vec![]);
+        let mut to = empty_asset();
+        let fn_to = add_function(&mut to, PackageIndex::new(0), "Tick", vec![]);
+
+        let missing = object_expression(PackageIndex::new(-3));
+        assert_eq!(
+            unresolved_references(&from, &missing),
+            [PackageIndex::new(-3)]
+        );
+        let err = super::checked_copy_expression(&from, &mut to, fn_from, fn_to, &missing)
+            .unwrap_err()
+            .to_string();
+        assert_eq!(
+            err,
+            "unresolved object references in Tick: import -3 is past the end of the 2 imports"
+        );
+        let broken = add_import(
+            &mut from,
+            "/Script/CoreUObject",
+            "Class",
+            PackageIndex::new(-9),
+            "Broken",
+        );
+        let broken = object_expression(broken);
+        let err = super::checked_copy_expression(&from, &mut to, fn_from, fn_to, &broken)
+            .unwrap_err()
+            .to_string();
+        assert!(
+            err.ends_with(r#"import -3 "Broken" has an outer that does not resolve"#),
+            "{err}"
+        );
+        assert!(to.imports.is_empty());
+
+        let ex = object_expression(actor);
+        assert!(unresolved_references(&from, &ex).is_empty());
+        let copied = super::checked_copy_expression(&from, &mut to, fn_from, fn_to, &ex).unwrap();
+        let KismetExpression::ExObjectConst(copied) = copied else {
+            panic!("expected object const");
+        };
+        assert_eq!(
+            super::resolve_object_const(&to, copied.value).as_deref(),
+            Some("/Script/Engine.Actor")
+        );
+    }
+
+    #[test]
+    fn expr_ctx() {
+        use crate::test_util::{add_import, empty_asset};
+        let mut asset = empty_asset();
+        let engine = add_import(
+            &mut asset,
+            "/Script/CoreUObject",
+            "Package",
+            PackageIndex::new(0),
+            "/Script/Engine",
+        );
+        let actor = add_import(&mut asset, "/Script/CoreUObject", "Class", engine, "Actor");
+        let function = add_function(&mut asset, PackageIndex::new(0), "Tick", vec![]);
+
+        let ctx = ExprCtx { asset: &asset };
+        assert_eq!(ctx.resolve_index(&actor), "Actor");
+        assert_eq!(ctx.resolve_index(&function), "Tick");
+        assert_eq!(
+            ctx.resolve_name(&FName::new_dummy("Foo".to_string(), 0)),
+            "Foo"
+        );
+        assert_eq!(ctx.resolve_index(&PackageIndex::new(0)), "null");
+        assert_eq!(
+            ctx.resolve_index(&PackageIndex::new(-5)),
+            "<invalid import -5>"
+        );
+        assert_eq!(
+            ctx.resolve_index(&PackageIndex::new(2)),
+            "<invalid export 2>"
+        );
+    }
+
+    fn add_function(
+        asset: &mut Asset<Cursor<Vec<u8>>>,
+        outer_index: PackageIndex,
+        name: &str,
+        bytecode: Vec<KismetExpression>,
+    ) -> PackageIndex {
+        use unreal_asset::exports::{BaseExport, FunctionExport, NormalExport, StructExport};
+        use unreal_asset::flags::EFunctionFlags;
+        use unreal_asset::uproperty::UField;
+        let base_export = BaseExport {
+            outer_index,
+            object_name: asset.add_fname(name),
+            ..Default::default()
+        };
+        let function = FunctionExport {
+            struct_export: StructExport {
+                normal_export: NormalExport {
+                    base_export,
+                    extras: vec![],
+                    properties: vec![],
+                },
+                field: UField { next: None },
+                super_struct: PackageIndex::new(0),
+                children: vec![],
+                loaded_properties: vec![],
+                script_bytecode: Some(bytecode),
+                script_bytecode_size: 0,
+                script_bytecode_raw: None,
+            },
+            function_flags: EFunctionFlags::empty(),
+        };
+        asset
+            .asset_data
+            .exports
+            .push(Export::FunctionExport(function));
+        PackageIndex::from_export(asset.asset_data.exports.len() as i32 - 1).unwrap()
+    }
+    fn import_engine_class(asset: &mut Asset<Cursor<Vec<u8>>>, class: &str) -> PackageIndex {
+        use crate::test_util::add_import;
+        let engine = add_import(
+            asset,
+            "/Script/CoreUObject",
+            "Package",
+            PackageIndex::new(0),
+            "/Script/Engine",
+        );
+        add_import(asset, "/Script/CoreUObject", "Class", engine, class)
+    }
+    /// copies the expression `build` makes from an asset importing `/Script/Engine.Actor` as
+    /// -2 into one that only imports `/Script/Engine.Pawn`, so the copy references -3
+    fn copy_actor_reference(
+        build: impl FnOnce(PackageIndex) -> KismetExpression,
+    ) -> (Asset<Cursor<Vec<u8>>>, KismetExpression) {
+        use crate::test_util::empty_asset;
+        let mut from = empty_asset();
+        let actor = import_engine_class(&mut from, "Actor");
+        let fn_from = add_function(&mut from, PackageIndex::new(0), "Tick", vec![]);
+        let mut to = empty_asset();
+        import_engine_class(&mut to, "Pawn");
+        let fn_to = add_function(&mut to, PackageIndex::new(0), "Tick", vec![]);
+        let ex = copy_expression(&from, &mut to, fn_from, fn_to, &build(actor));
+        assert_eq!(to.imports.len(), 3, "the package import is reused");
+        (to, ex)
+    }
+    fn version() -> AssetVersion {
+        AssetVersion {
+            version: ObjectVersion::VER_UE4_AUTOMATIC_VERSION,
+            version_ue5: ObjectVersionUE5::UNKNOWN,
+        }
+    }
+    fn prop(owner: i32) -> KismetPropertyPointer {
+        KismetPropertyPointer {
+            old: None,
+            new: Some(FieldPath {
+                path: vec![],
+                resolved_owner: PackageIndex::new(owner),
+            }),
+        }
+    }
+    fn statement(offset: usize, ex: impl Into<KismetExpression>) -> TrackedStatement {
+        TrackedStatement {
+            origin: (None, PackageIndex::new(1)),
+            points_to: None,
+            original_offset: Some(offset),
+            merged_offsets: vec![],
+            ex: ex.into(),
+        }
+    }
+    fn jump(code_offset: u32) -> ExJump {
+        ExJump {
+            token: EExprToken::ExJump,
+            code_offset,
+        }
+    }
+    fn jump_if_not(code_offset: u32, condition: bool) -> ExJumpIfNot {
+        let condition = if condition {
+            ExTrue {
+                token: EExprToken::ExTrue,
+            }
+            .into()
+        } else {
+            ExFalse {
+                token: EExprToken::ExFalse,
+            }
+            .into()
+        };
+        ExJumpIfNot {
+            token: EExprToken::ExJumpIfNot,
+            code_offset,
+            boolean_expression: Box::new(condition),
+        }
+    }
+    fn nothing() -> ExNothing {
+        ExNothing {
+            token: EExprToken::ExNothing,
+        }
+    }
+
+    #[test]
+    fn optimize_jump_chain() {
+        let mut statements = vec![
+            statement(0, jump(10)),
+            statement(5, nothing()),
+            statement(10, jump(20)),
+            statement(15, nothing()),
+            statement(20, jump(30)),
+            statement(30, nothing()),
+        ];
+        optimize_bytecode(&mut statements);
+
+        let targets: Vec<_> = statements
+            .iter()
+            .filter_map(|inst| match &inst.ex {
+                KismetExpression::ExJump(ex) => Some(ex.code_offset),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(targets, [30, 30, 30]);
+    }
+
+    #[test]
+    fn optimize_constant_conditions() {
+        let mut statements = vec![
+            statement(0, jump(6)),
+            statement(5, jump_if_not(20, true)),
+            statement(6, jump_if_not(20, false)),
+            statement(15, nothing()),
+            statement(20, nothing()),
+        ];
+        optimize_bytecode(&mut statements);
+
+        assert_eq!(statements.len(), 4);
+        // never taken, so removed
+        assert!(!statements
+            .iter()
+            .any(|inst| matches!(inst.ex, KismetExpression::ExJumpIfNot(_))));
+        // always taken, so unconditional and jumps to it are threaded through
+        assert_eq!(statements[1].merged_offsets, [5]);
+        for inst in &statements[..2] {
+            let KismetExpression::ExJump(ex) = &inst.ex else {
+                panic!("expected jump");
+            };
+            assert_eq!(ex.code_offset, 20);
+        }
+    }
+}
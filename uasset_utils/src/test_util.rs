@@ -0,0 +1,227 @@
+//! In-memory registries and assets shared by the crate's tests
+
+use std::io::Cursor;
+
+use unreal_asset::engine_version::EngineVersion;
+use unreal_asset::exports::{BaseExport, Export};
+use unreal_asset::flags::EObjectFlags;
+use unreal_asset::types::PackageIndex;
+use unreal_asset::{Asset, AssetBuilder, Import};
+
+use crate::asset_registry::*;
+
+/// Serialized form of [`sample_registry`]
+pub(crate) const SAMPLE_REGISTRY_BIN: &[u8] =
+    include_bytes!("../tests/fixtures/sample_registry.bin");
+
+/// Empty registry with the given names already in the name table
+pub(crate) fn registry(names: &[&str]) -> AssetRegistry {
+    AssetRegistry {
+        version: [0; 16],
+        version_int: 0,
+        hash_version: 0,
+        names: Names::new(names.iter().map(|n| n.to_string()).collect()),
+        store: Store::default(),
+        asset_data: vec![],
+        dependencies: DependencySection::Parsed(Dependencies::default()),
+        trailing: vec![],
+    }
+}
+
+/// Asset data for `package_name` whose object is named after the package, not yet added to
+/// `ar`
+pub(crate) fn asset(ar: &mut AssetRegistry, package_name: &str, asset_class: &str) -> AssetData {
+    let (package_path, name) = package_name.rsplit_once('/').unwrap();
+    AssetData::builder()
+        .object_path(ar.get_name(&format!("{package_name}.{name}")))
+        .package_path(ar.get_name(package_path))
+        .asset_class(ar.get_name(asset_class))
+        .package_name(ar.get_name(package_name))
+        .asset_name(ar.get_name(name))
+        .build()
+        .unwrap()
+}
+
+/// Small registry with a Blueprint, its generated class and a texture, with tags and chunk
+/// ids set. [`SAMPLE_REGISTRY_BIN`] is this registry written out.
+pub(crate) fn sample_registry() -> AssetRegistry {
+    let mut ar = registry(&[]);
+    ar.version_int = 1;
+
+    let blueprint = asset(&mut ar, "/Game/Foo/Bar", "Blueprint");
+    let generated = AssetData::builder()
+        .object_path(ar.get_name("/Game/Foo/Bar.Bar_C"))
+        .package_path(blueprint.package_path)
+        .asset_class(ar.get_name("BlueprintGeneratedClass"))
+        .package_name(blueprint.package_name)
+        .asset_name(ar.get_name("Bar_C"))
+        .build()
+        .unwrap();
+    let texture = asset(&mut ar, "/Game/Textures/T_Rock", "Texture2D");
+    ar.asset_data.extend([blueprint, generated, texture]);
+
+    ar.add_tags(0, [("ParentClass", "/Script/Engine.Actor")])
+        .unwrap();
+    ar.add_tags(2, [("Format", "BC1"), ("DisplayName", "Fels\u{f6}")])
+        .unwrap();
+    ar.set_chunk_ids("/Game/Foo/Bar.Bar", &[0, 3]);
+    ar.set_chunk_ids("/Game/Textures/T_Rock.T_Rock", &[1]);
+    ar
+}
+
+/// UE 4.27 asset with empty name, import and export tables, parsed from a bare package
+/// summary. Tests add the imports and exports they need.
+pub(crate) fn empty_asset() -> Asset<Cursor<Vec<u8>>> {
+    fn ints(w: &mut Vec<u8>, values: &[i32]) {
+        values.iter().for_each(|v| w.extend(v.to_le_bytes()));
+    }
+    fn engine_version(w: &mut Vec<u8>) {
+        [4u16, 27, 0].iter().for_each(|v| w.extend(v.to_le_bytes()));
+        // changelist and an empty branch name
+        ints(w, &[0, 0]);
+    }
+    let mut w = vec![0xc1, 0x83, 0x2a, 0x9e];
+    // legacy, UE3, UE4 and licensee versions, then no custom versions
+    ints(&mut w, &[-7, 864, 522, 0, 0]);
+    let header_size = w.len();
+    ints(&mut w, &[0]);
+    // folder name
+    ints(&mut w, &[5]);
+    w.extend(b"None\0");
+    // PKG_FilterEditorOnly, so there is no localization id or package owner
+    w.extend(0x8000_0000u32.to_le_bytes());
+    // name, gatherable text, export and import tables, depends map, soft package references,
+    // searchable names and thumbnail table
+    ints(&mut w, &[0; 13]);
+    // guid and generations
+    w.extend([0; 16]);
+    ints(&mut w, &[0]);
+    // saved by and compatible engine versions
+    engine_version(&mut w);
+    engine_version(&mut w);
+    // compression flags and chunks, package source, additional packages to cook and asset
+    // registry data
+    ints(&mut w, &[0; 5]);
+    // bulk data start
+    w.extend(0i64.to_le_bytes());
+    // world tile info, chunk ids and preload dependencies
+    ints(&mut w, &[0; 4]);
+    let len = w.len() as i32;
+    w[header_size..header_size + 4].copy_from_slice(&len.to_le_bytes());
+
+    AssetBuilder::new(Cursor::new(w), EngineVersion::VER_UE4_27)
+        .build()
+        .unwrap()
+}
+
+/// Adds an import named `object_name` of class `class_package.class_name` to `asset`
+pub(crate) fn add_import(
+    asset: &mut Asset<Cursor<Vec<u8>>>,
+    class_package: &str,
+    class_name: &str,
+    outer_index: PackageIndex,
+    object_name: &str,
+) -> PackageIndex {
+    let import = Import {
+        class_package: asset.add_fname(class_package),
+        class_name: asset.add_fname(class_name),
+        outer_index,
+        object_name: asset.add_fname(object_name),
+        optional: false,
+    };
+    asset.add_import(import)
+}
+
+/// Adds a public export named `object_name` whose class is `class_index` to `asset`
+pub(crate) fn add_export(
+    asset: &mut Asset<Cursor<Vec<u8>>>,
+    class_index: PackageIndex,
+    outer_index: PackageIndex,
+    object_name: &str,
+) -> PackageIndex {
+    let export = BaseExport {
+        class_index,
+        outer_index,
+        object_name: asset.add_fname(object_name),
+        object_flags: EObjectFlags::RF_PUBLIC,
+        ..Default::default()
+    };
+    asset.asset_data.exports.push(Export::BaseExport(export));
+    PackageIndex::from_export(asset.asset_data.exports.len() as i32 - 1).unwrap()
+}
+
+/// [`empty_asset`] holding a public `Bar_C` export of the imported class
+/// `/Script/Engine.BlueprintGeneratedClass`
+pub(crate) fn generated_class_asset() -> Asset<Cursor<Vec<u8>>> {
+    let mut asset = empty_asset();
+    let engine = add_import(
+        &mut asset,
+        "/Script/CoreUObject",
+        "Package",
+        PackageIndex::new(0),
+        "/Script/Engine",
+    );
+    let class = add_import(
+        &mut asset,
+        "/Script/CoreUObject",
+        "Class",
+        engine,
+        "BlueprintGeneratedClass",
+    );
+    add_export(&mut asset, class, PackageIndex::new(0), "Bar_C");
+    asset
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use unreal_asset::exports::ExportBaseTrait;
+    use unreal_asset::reader::ArchiveTrait;
+
+    #[test]
+    fn sample_registry_fixture() {
+        let ar = AssetRegistry::read_from_slice(SAMPLE_REGISTRY_BIN).unwrap();
+        assert_eq!(ar, sample_registry());
+
+        assert_eq!(ar.asset_data.len(), 3);
+        let bar = ar.resolve(&ar.asset_data[1]);
+        assert_eq!(bar.object_path(), "/Game/Foo/Bar.Bar_C");
+        assert_eq!(bar.asset_class(), "BlueprintGeneratedClass");
+        assert_eq!(bar.package_name(), "/Game/Foo/Bar");
+        assert_eq!(ar.asset_data[1].chunk_ids, [0, 3]);
+
+        let rock = ar.resolve(&ar.asset_data[2]);
+        assert_eq!(rock.package_path(), "/Game/Textures");
+        assert_eq!(
+            rock.tag_value("DisplayName"),
+            Some(TagValue::WideString("Fels\u{f6}".to_string()))
+        );
+        assert_eq!(
+            rock.tag_value("Format"),
+            Some(TagValue::AnsiString("BC1".to_string()))
+        );
+    }
+
+    #[test]
+    fn generated_class_asset() {
+        let asset = super::generated_class_asset();
+        assert_eq!(asset.imports.len(), 2);
+        let root = get_root_export(&asset).unwrap();
+        let root = asset.get_export(root).unwrap().get_base_export();
+        assert_eq!(root.object_name.get_owned_content(), "Bar_C");
+        assert_eq!(
+            asset
+                .get_import(root.class_index)
+                .unwrap()
+                .object_name
+                .get_owned_content(),
+            "BlueprintGeneratedClass"
+        );
+    }
+
+    #[test]
+    fn sample_registry_roundtrip() {
+        let bytes = sample_registry().write_to_vec().unwrap();
+        assert_eq!(bytes, SAMPLE_REGISTRY_BIN);
+    }
+}